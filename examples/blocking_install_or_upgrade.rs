@@ -1,7 +1,6 @@
 use helm_wrapper_rs::blocking::{DefaultHelmExecutor, HelmExecutor};
 use helm_wrapper_rs::HelmDeployStatus;
 use non_blank_string_rs::NonBlankString;
-use std::collections::HashMap; // If you need to provide overrides
 use log::LevelFilter;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -17,8 +16,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let chart_name: NonBlankString = "cowboysysop/whoami".parse().unwrap();
     // Optionally, specify chart version, values, etc.
     // let chart_version: Option<&NonBlankString> = Some(&"1.0.0".parse().unwrap());
-    // let mut values_overrides = HashMap::new();
-    // values_overrides.insert("replicaCount".parse().unwrap(), "2".to_string());
+    // let values_overrides = vec![("replicaCount".parse().unwrap(), "2".to_string())];
 
     let status: HelmDeployStatus = helm_executor.install_or_upgrade(
         &namespace,
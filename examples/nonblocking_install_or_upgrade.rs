@@ -1,7 +1,6 @@
 use helm_wrapper_rs::nonblocking::{DefaultHelmExecutor, HelmExecutor};
 use helm_wrapper_rs::HelmDeployStatus;
 use non_blank_string_rs::NonBlankString;
-use std::collections::HashMap; // If you need to provide overrides
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -12,8 +11,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let chart_name: NonBlankString = "cowboysysop/whoami".parse().unwrap();
     // Optionally, specify chart version, values, etc.
     // let chart_version: Option<&NonBlankString> = Some(&"1.0.0".parse().unwrap());
-    // let mut values_overrides = HashMap::new();
-    // values_overrides.insert("replicaCount".parse().unwrap(), "2".to_string());
+    // let values_overrides = vec![("replicaCount".parse().unwrap(), "2".to_string())];
 
     let status: HelmDeployStatus = helm_executor.install_or_upgrade(
         &namespace,
@@ -0,0 +1,15 @@
+#!/bin/bash
+
+set -e
+
+# Runs integration tests against `fake-helm`, no helm or Kubernetes cluster required.
+
+cargo build --features "fake-helm" --bin fake-helm
+
+export HELM_PATH="$PWD/target/debug/fake-helm"
+export FAKE_HELM_STATE_DIR=$(mktemp -d)
+
+trap 'rm -rf "$FAKE_HELM_STATE_DIR"' EXIT
+
+cargo test --no-default-features --features "blocking"
+cargo test --no-default-features --features "nonblocking"
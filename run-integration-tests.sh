@@ -2,9 +2,7 @@
 
 set -e
 
-# Install `whoami` before running tests:
-# helm repo add cowboysysop https://cowboysysop.github.io/charts/
-# helm repo update
+# Tests add `cowboysysop` repository themselves. Install `whoami` before running tests:
 # helm upgrade --install -n whoami whoami cowboysysop/whoami
 
 export KUBECONFIG=~/.kube/dev.kubeconfig
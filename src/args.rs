@@ -0,0 +1,181 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+};
+
+use crate::redact::{Redactor, REDACTED};
+
+/// Helm command line arguments.
+///
+/// Every argument is kept as a separate item, so paths and values containing
+/// spaces, `=`, commas or quotes reach helm exactly as they were given.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct HelmArgs(Vec<OsString>);
+
+impl HelmArgs {
+    /// Start arguments with helm subcommand, e.g. `ls` or `upgrade`
+    pub fn new(subcommand: &str) -> Self {
+        Self(vec![subcommand.into()])
+    }
+
+    /// Append single argument
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.0.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Append flag without value (e.g. `--wait`) if `enabled`
+    pub fn flag(&mut self, name: &str, enabled: bool) -> &mut Self {
+        if enabled {
+            self.arg(name);
+        }
+        self
+    }
+
+    /// Append option with value as two separate arguments: `<name> <value>`
+    pub fn option(&mut self, name: &str, value: impl AsRef<OsStr>) -> &mut Self {
+        self.arg(name).arg(value)
+    }
+
+    /// Helm subcommand, e.g. `upgrade`
+    pub fn subcommand(&self) -> String {
+        self.0
+            .first()
+            .map(|subcommand| subcommand.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &OsString> {
+        self.0.iter()
+    }
+
+    /// Copy for logs: sensitive `key=value` pairs, values of sensitive options
+    /// (e.g. `--password VALUE`) and secrets are masked
+    pub fn redacted(&self, redactor: &Redactor) -> HelmArgs {
+        let mut previous: Option<String> = None;
+
+        let args = self
+            .0
+            .iter()
+            .map(|arg| {
+                let arg = arg.to_string_lossy().to_string();
+
+                let sensitive_option_value = !arg.starts_with('-')
+                    && previous.as_deref().is_some_and(|previous| {
+                        previous.starts_with("--") && redactor.is_sensitive_key(previous)
+                    });
+
+                let redacted = if sensitive_option_value {
+                    REDACTED.to_string()
+                } else {
+                    redactor.redact_text(&arg)
+                };
+
+                previous = Some(arg);
+
+                OsString::from(redacted)
+            })
+            .collect();
+
+        HelmArgs(args)
+    }
+}
+
+impl FromIterator<OsString> for HelmArgs {
+    fn from_iter<T: IntoIterator<Item = OsString>>(iter: T) -> Self {
+        HelmArgs(iter.into_iter().collect())
+    }
+}
+
+/// Shell-like representation for logs, arguments with special chars are single-quoted
+impl fmt::Display for HelmArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, arg) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+
+            let arg = arg.to_string_lossy();
+
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+
+            if plain {
+                write!(f, "{arg}")?;
+            } else {
+                write!(f, "'{}'", arg.replace('\'', r"'\''"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod args_tests {
+    use std::ffi::OsString;
+
+    use crate::{args::HelmArgs, redact::Redactor};
+
+    #[test]
+    fn option_value_is_kept_as_single_argument() {
+        let mut args = HelmArgs::new("upgrade");
+        args.option("-f", "/tmp/my values/values prod.yaml")
+            .option("--set", "a=b c, 'd' \"e\"");
+
+        assert_eq!(
+            args.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                OsString::from("upgrade"),
+                OsString::from("-f"),
+                OsString::from("/tmp/my values/values prod.yaml"),
+                OsString::from("--set"),
+                OsString::from("a=b c, 'd' \"e\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn flag_is_added_only_when_enabled() {
+        let mut args = HelmArgs::new("uninstall");
+        args.flag("--wait", true).flag("--debug", false);
+
+        assert_eq!(
+            args.iter().cloned().collect::<Vec<_>>(),
+            vec![OsString::from("uninstall"), OsString::from("--wait")]
+        );
+    }
+
+    #[test]
+    fn display_quotes_arguments_with_special_chars() {
+        let mut args = HelmArgs::new("upgrade");
+        args.arg("--install")
+            .option("--kubeconfig", "/home/user/my config")
+            .option("--set", "key=it's")
+            .option("--set", "key=a,b");
+
+        assert_eq!(
+            args.to_string(),
+            r"upgrade --install --kubeconfig '/home/user/my config' --set 'key=it'\''s' --set key=a,b"
+        );
+    }
+
+    #[test]
+    fn redacted_masks_sensitive_values() {
+        let redactor = Redactor::new().sensitive_key("*password*").secret("s3cr3t");
+
+        let mut args = HelmArgs::new("upgrade");
+        args.option("--set", "db.password=qwerty,replicaCount=2")
+            .option("--set-json", r#"cfg={"adminPassword":"x","user":"u"}"#)
+            .option("--password", "p4ss")
+            .option("--description", "token s3cr3t")
+            .flag("--wait", true);
+
+        assert_eq!(
+            args.redacted(&redactor).to_string(),
+            r#"upgrade --set 'db.password=***,replicaCount=2' --set-json 'cfg={"adminPassword":***,"user":"u"}' --password '***' --description 'token ***' --wait"#
+        );
+    }
+}
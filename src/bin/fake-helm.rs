@@ -0,0 +1,745 @@
+//! Fake `helm` for offline integration tests, emulates the CLI surface used by
+//! `helm-wrapper-rs`:
+//!
+//! - `ls [-n NAMESPACE] -o json`
+//! - `upgrade --install -n NAMESPACE RELEASE REPO/CHART [--version VERSION] [-f FILE]... -o json`
+//! - `uninstall RELEASE -n NAMESPACE`
+//! - `repo add NAME URL`, `repo update [NAME]...`, `repo list -o json`, `repo remove NAME...`
+//!
+//! State is kept in directory from `FAKE_HELM_STATE_DIR` which is required, so parallel
+//! test runs don't share releases.
+//! Values files drive error modes: file which isn't valid YAML fails like real helm,
+//! top-level `fakeHelmError: <message>` fails upgrade with `Error: <message>`.
+//!
+//! Run tests with it:
+//!
+//! ```bash
+//! cargo build --features fake-helm --bin fake-helm
+//! HELM_PATH=target/debug/fake-helm FAKE_HELM_STATE_DIR=$(mktemp -d) cargo test
+//! ```
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Charts available in every repository: name, version, app version. The first one is the latest.
+const CHARTS: &[(&str, &str, &str)] =
+    &[("whoami", "5.2.0", "1.10.3"), ("whoami", "5.1.0", "1.10.2")];
+
+/// Options which take value, other options are flags
+const VALUE_OPTIONS: &[&str] = &[
+    "-n",
+    "--namespace",
+    "-f",
+    "--values",
+    "--set",
+    "--set-string",
+    "--set-json",
+    "--set-file",
+    "--set-literal",
+    "--version",
+    "-o",
+    "--output",
+    "--timeout",
+    "--kubeconfig",
+    "--kube-context",
+    "--description",
+    "--labels",
+    "--username",
+    "--ca-file",
+    "--cert-file",
+    "--key-file",
+];
+
+const DEFAULT_NAMESPACE: &str = "default";
+
+/// Error key in values file, its value is returned as helm error
+const ERROR_KEY: &str = "fakeHelmError";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = state_dir().and_then(|state_dir| run(&Args::parse(&args), &state_dir));
+
+    match result {
+        Ok(stdout) => {
+            print!("{stdout}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("Error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn state_dir() -> Result<PathBuf, String> {
+    env::var_os("FAKE_HELM_STATE_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| "FAKE_HELM_STATE_DIR environment variable is required".to_string())
+}
+
+/// Parsed command line: positional arguments and options in the given order
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Self {
+        let mut parsed = Args::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                parsed.positional.push(arg.clone());
+            } else if let Some((name, value)) = arg.split_once('=') {
+                parsed
+                    .options
+                    .push((name.to_string(), Some(value.to_string())));
+            } else if VALUE_OPTIONS.contains(&arg.as_str()) {
+                parsed.options.push((arg.clone(), args.next().cloned()));
+            } else {
+                parsed.options.push((arg.clone(), None));
+            }
+        }
+
+        parsed
+    }
+
+    fn values(&self, names: &[&str]) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(name, _)| names.contains(&name.as_str()))
+            .filter_map(|(_, value)| value.as_deref())
+            .collect()
+    }
+
+    fn value(&self, names: &[&str]) -> Option<&str> {
+        self.values(names).last().copied()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    fn namespace(&self) -> &str {
+        self.value(&["-n", "--namespace"])
+            .unwrap_or(DEFAULT_NAMESPACE)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct State {
+    repositories: Vec<Repository>,
+    releases: Vec<Release>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Repository {
+    name: String,
+    url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Release {
+    name: String,
+    namespace: String,
+    /// Oldest first
+    revisions: Vec<Revision>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Revision {
+    revision: u32,
+    status: String,
+    chart: String,
+    chart_version: String,
+    app_version: String,
+    updated: String,
+    description: String,
+}
+
+fn run(args: &Args, state_dir: &Path) -> Result<String, String> {
+    let positional: Vec<&str> = args.positional.iter().map(String::as_str).collect();
+
+    match positional.as_slice() {
+        ["ls"] | ["list"] => with_state(state_dir, |state| Ok(list(state, args))),
+        ["upgrade", release_name, chart] => {
+            with_state(state_dir, |state| upgrade(state, args, release_name, chart))
+        }
+        ["uninstall", release_name] => {
+            with_state(state_dir, |state| uninstall(state, args, release_name))
+        }
+        ["repo", "add", name, url] => {
+            with_state(state_dir, |state| repo_add(state, args, name, url))
+        }
+        ["repo", "update", names @ ..] => with_state(state_dir, |state| repo_update(state, names)),
+        ["repo", "list"] | ["repo", "ls"] => with_state(state_dir, |state| repo_list(state)),
+        ["repo", "remove", names @ ..] | ["repo", "rm", names @ ..] if !names.is_empty() => {
+            with_state(state_dir, |state| repo_remove(state, names))
+        }
+        ["version"] => Ok("v3.15.0+fake\n".to_string()),
+        _ => Err(format!(
+            "fake-helm doesn't support 'helm {}'",
+            args.positional.join(" ")
+        )),
+    }
+}
+
+/// Run command with exclusive access to state, state is saved if command succeeds
+fn with_state(
+    state_dir: &Path,
+    command: impl FnOnce(&mut State) -> Result<String, String>,
+) -> Result<String, String> {
+    fs::create_dir_all(state_dir).map_err(|e| e.to_string())?;
+
+    // Advisory lock is released by OS when process exits, even if it was killed
+    let lock = fs::File::create(state_dir.join("state.lock")).map_err(|e| e.to_string())?;
+    lock.lock().map_err(|e| e.to_string())?;
+
+    let state_path = state_dir.join("state.json");
+
+    let mut state: State = match fs::read_to_string(&state_path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string())?,
+        Err(_) => State::default(),
+    };
+
+    let stdout = command(&mut state)?;
+
+    let content = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    fs::write(&state_path, content).map_err(|e| e.to_string())?;
+
+    Ok(stdout)
+}
+
+fn list(state: &State, args: &Args) -> String {
+    let namespace = args.namespace();
+
+    let releases: Vec<serde_json::Value> = state
+        .releases
+        .iter()
+        .filter(|release| release.namespace == namespace)
+        .filter_map(|release| {
+            release.revisions.last().map(|revision| {
+                json!({
+                    "name": release.name,
+                    "namespace": release.namespace,
+                    "revision": revision.revision.to_string(),
+                    "updated": revision.updated,
+                    "status": revision.status,
+                    "chart": format!("{}-{}", revision.chart, revision.chart_version),
+                    "app_version": revision.app_version,
+                })
+            })
+        })
+        .collect();
+
+    format!("{}\n", serde_json::Value::Array(releases))
+}
+
+fn upgrade(
+    state: &mut State,
+    args: &Args,
+    release_name: &str,
+    chart: &str,
+) -> Result<String, String> {
+    let namespace = args.namespace().to_string();
+
+    for values_file in args.values(&["-f", "--values"]) {
+        check_values_file(Path::new(values_file))?;
+    }
+
+    let (chart_name, chart_version, app_version) =
+        resolve_chart(state, chart, args.value(&["--version"]))?;
+
+    let release_index = state
+        .releases
+        .iter()
+        .position(|release| release.name == release_name && release.namespace == namespace);
+
+    let previous = release_index.and_then(|index| state.releases[index].revisions.last());
+
+    if previous.is_none() && !args.flag("--install") {
+        return Err(format!(
+            "UPGRADE FAILED: \"{release_name}\" has no deployed releases"
+        ));
+    }
+
+    if previous.is_some_and(|previous| previous.status.starts_with("pending")) {
+        return Err(
+            "UPGRADE FAILED: another operation (install/upgrade/rollback) is in progress"
+                .to_string(),
+        );
+    }
+
+    let dry_run = args.options.iter().any(|(name, _)| name == "--dry-run");
+
+    let (status, description) = match (previous.is_some(), dry_run) {
+        (false, false) => ("deployed", "Install complete"),
+        (true, false) => ("deployed", "Upgrade complete"),
+        (false, true) => ("pending-install", "Dry run complete"),
+        (true, true) => ("pending-upgrade", "Dry run complete"),
+    };
+
+    let revision = Revision {
+        revision: previous.map_or(1, |previous| previous.revision + 1),
+        status: status.to_string(),
+        chart: chart_name,
+        chart_version,
+        app_version,
+        updated: now(),
+        description: args
+            .value(&["--description"])
+            .unwrap_or(description)
+            .to_string(),
+    };
+
+    let output = json!({
+        "name": release_name,
+        "namespace": namespace,
+        "version": revision.revision,
+        "info": {
+            "first_deployed": revision.updated,
+            "last_deployed": revision.updated,
+            "deleted": "",
+            "description": revision.description,
+            "status": revision.status,
+        },
+        "chart": {
+            "metadata": {
+                "name": revision.chart,
+                "version": revision.chart_version,
+                "appVersion": revision.app_version,
+            },
+            "values": {},
+        },
+        "config": {},
+        "manifest": "",
+    });
+
+    if !dry_run {
+        let release = match release_index {
+            Some(index) => &mut state.releases[index],
+            None => {
+                state.releases.push(Release {
+                    name: release_name.to_string(),
+                    namespace: namespace.clone(),
+                    revisions: vec![],
+                });
+                state.releases.last_mut().unwrap()
+            }
+        };
+
+        for previous in release.revisions.iter_mut() {
+            if previous.status == "deployed" {
+                previous.status = "superseded".to_string();
+            }
+        }
+
+        release.revisions.push(revision);
+    }
+
+    Ok(format!("{output}\n"))
+}
+
+/// Chart name, version and app version of `REPO/CHART` reference
+fn resolve_chart(
+    state: &State,
+    chart: &str,
+    version: Option<&str>,
+) -> Result<(String, String, String), String> {
+    let Some((repo, chart_name)) = chart.split_once('/') else {
+        return Err(format!(
+            "non-absolute URLs should be in form of repo_name/path_to_chart, got: {chart}"
+        ));
+    };
+
+    if !state.repositories.iter().any(|r| r.name == repo) {
+        return Err(format!("repo {repo} not found"));
+    }
+
+    CHARTS
+        .iter()
+        .find(|(name, chart_version, _)| {
+            *name == chart_name && version.is_none_or(|version| version == *chart_version)
+        })
+        .map(|(name, chart_version, app_version)| {
+            (
+                name.to_string(),
+                chart_version.to_string(),
+                app_version.to_string(),
+            )
+        })
+        .ok_or_else(|| match version {
+            Some(version) => format!(
+                "chart \"{chart_name}\" matching {version} not found in {repo} index. (try 'helm repo update'): no chart version found for {chart_name}-{version}"
+            ),
+            None => format!("chart \"{chart_name}\" not found in {repo} index. (try 'helm repo update'): no chart name found"),
+        })
+}
+
+/// Fail like helm on unreadable or malformed values file, or with programmed error
+fn check_values_file(path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|_| format!("open {}: no such file or directory", path.display()))?;
+
+    if let Ok(serde_json::Value::Object(values)) = serde_json::from_str(&content) {
+        return match values.get(ERROR_KEY).and_then(|error| error.as_str()) {
+            Some(error) => Err(error.to_string()),
+            None => Ok(()),
+        };
+    }
+
+    if let Some(line) = invalid_yaml_line(&content) {
+        return Err(format!(
+            "failed to parse {}: error converting YAML to JSON: yaml: line {line}: could not find expected ':'",
+            path.display()
+        ));
+    }
+
+    for line in content.lines() {
+        if let Some(error) = line
+            .strip_prefix(ERROR_KEY)
+            .and_then(|rest| rest.strip_prefix(':'))
+        {
+            return Err(error.trim().trim_matches(['"', '\'']).to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of the first line which isn't mapping entry, sequence item or part of
+/// block scalar. It's enough to detect broken values files, not a YAML parser.
+fn invalid_yaml_line(content: &str) -> Option<usize> {
+    let mut block_scalar_indent: Option<usize> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+
+        if let Some(block_indent) = block_scalar_indent {
+            if indent > block_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+
+        let item = trimmed.strip_prefix('-').map_or(trimmed, str::trim_start);
+
+        let valid = trimmed == "-"
+            || trimmed.starts_with("- ")
+            || item.contains(": ")
+            || item.ends_with(':')
+            || item.starts_with(['{', '['])
+            || (indent > 0 && index > 0);
+
+        if !valid {
+            return Some(index + 1);
+        }
+
+        if ["|", "|-", "|+", ">", ">-", ">+"]
+            .iter()
+            .any(|indicator| item.ends_with(&format!(": {indicator}")))
+        {
+            block_scalar_indent = Some(indent);
+        }
+    }
+
+    None
+}
+
+fn uninstall(state: &mut State, args: &Args, release_name: &str) -> Result<String, String> {
+    let namespace = args.namespace();
+    let count = state.releases.len();
+
+    state
+        .releases
+        .retain(|release| !(release.name == release_name && release.namespace == namespace));
+
+    if state.releases.len() < count {
+        Ok(format!("release \"{release_name}\" uninstalled\n"))
+    } else {
+        Err(format!(
+            "uninstall: Release not loaded: {release_name}: release: not found"
+        ))
+    }
+}
+
+fn repo_add(state: &mut State, args: &Args, name: &str, url: &str) -> Result<String, String> {
+    match state.repositories.iter_mut().find(|r| r.name == name) {
+        Some(existing) if existing.url == url => {
+            return Ok(format!(
+                "\"{name}\" already exists with the same configuration, skipping\n"
+            ))
+        }
+        Some(existing) if args.flag("--force-update") => existing.url = url.to_string(),
+        Some(_) => {
+            return Err(format!(
+                "repository name ({name}) already exists, please specify a different name"
+            ))
+        }
+        None => state.repositories.push(Repository {
+            name: name.to_string(),
+            url: url.to_string(),
+        }),
+    }
+
+    Ok(format!("\"{name}\" has been added to your repositories\n"))
+}
+
+fn repo_update(state: &State, names: &[&str]) -> Result<String, String> {
+    if state.repositories.is_empty() {
+        return Err("no repositories found. You must add one before updating".to_string());
+    }
+
+    let unknown: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| !state.repositories.iter().any(|r| r.name == *name))
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(format!(
+            "no repositories found matching '{}'.  Nothing will be updated",
+            unknown.join(", ")
+        ));
+    }
+
+    let mut stdout =
+        "Hang tight while we grab the latest from your chart repositories...\n".to_string();
+
+    for repository in &state.repositories {
+        if names.is_empty() || names.contains(&repository.name.as_str()) {
+            stdout.push_str(&format!(
+                "...Successfully got an update from the \"{}\" chart repository\n",
+                repository.name
+            ));
+        }
+    }
+
+    stdout.push_str("Update Complete. ⎈Happy Helming!⎈\n");
+
+    Ok(stdout)
+}
+
+fn repo_list(state: &State) -> Result<String, String> {
+    if state.repositories.is_empty() {
+        return Err("no repositories to show".to_string());
+    }
+
+    let repositories: Vec<serde_json::Value> = state
+        .repositories
+        .iter()
+        .map(|r| json!({"name": r.name, "url": r.url}))
+        .collect();
+
+    Ok(format!("{}\n", serde_json::Value::Array(repositories)))
+}
+
+fn repo_remove(state: &mut State, names: &[&str]) -> Result<String, String> {
+    let mut stdout = String::new();
+
+    for name in names {
+        let count = state.repositories.len();
+        state.repositories.retain(|r| r.name != *name);
+
+        if state.repositories.len() == count {
+            return Err(format!("no repo named \"{name}\" found"));
+        }
+
+        stdout.push_str(&format!(
+            "\"{name}\" has been removed from your repositories\n"
+        ));
+    }
+
+    Ok(stdout)
+}
+
+/// Current time in helm format: `2025-06-01 10:00:00.123456789 +0000 UTC`
+fn now() -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let seconds = elapsed.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:09} +0000 UTC",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        elapsed.subsec_nanos()
+    )
+}
+
+/// Date from days since 1970-01-01, Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod fake_helm_tests {
+    use std::path::Path;
+
+    use crate::{civil_from_days, invalid_yaml_line, run, Args};
+
+    fn args(line: &str) -> Args {
+        let args: Vec<String> = line.split(' ').map(str::to_string).collect();
+        Args::parse(&args)
+    }
+
+    #[test]
+    fn parse_options_with_values() {
+        let args = args("upgrade --install -n whoami whoami cowboysysop/whoami --version 5.2.0 --dry-run=client --debug");
+
+        assert_eq!(
+            args.positional,
+            vec!["upgrade", "whoami", "cowboysysop/whoami"]
+        );
+        assert_eq!(args.namespace(), "whoami");
+        assert_eq!(args.value(&["--version"]), Some("5.2.0"));
+        assert_eq!(args.value(&["--dry-run"]), Some("client"));
+        assert!(args.flag("--debug"));
+        assert!(!args.flag("--wait"));
+    }
+
+    #[test]
+    fn detect_invalid_yaml() {
+        assert_eq!(invalid_yaml_line("wrong\nvalues\n"), Some(1));
+        assert_eq!(
+            invalid_yaml_line(
+                "ingress:\n  hosts:\n    - host: whoami.test.lab\n      paths:\n        - /\n"
+            ),
+            None
+        );
+        assert_eq!(
+            invalid_yaml_line("# comment\nnotes: |\n  free text\n  more text\nreplicaCount: 2\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn days_to_date() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(20_240), (2025, 6, 1));
+    }
+
+    #[test]
+    fn install_upgrade_and_uninstall() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_dir = dir.path();
+
+        let install = args("upgrade --install -n apps whoami cowboysysop/whoami -o json");
+
+        assert_eq!(
+            run(&install, state_dir).unwrap_err(),
+            "repo cowboysysop not found"
+        );
+
+        run(
+            &args("repo add cowboysysop https://cowboysysop.github.io/charts/"),
+            state_dir,
+        )
+        .unwrap();
+
+        run(&install, state_dir).unwrap();
+
+        let output: serde_json::Value =
+            serde_json::from_str(&run(&install, state_dir).unwrap()).unwrap();
+
+        assert_eq!(output["version"], 2);
+        assert_eq!(output["info"]["status"], "deployed");
+
+        let releases: serde_json::Value =
+            serde_json::from_str(&run(&args("ls -n apps -o json"), state_dir).unwrap()).unwrap();
+
+        assert_eq!(releases[0]["revision"], "2");
+        assert_eq!(releases[0]["chart"], "whoami-5.2.0");
+        assert_eq!(releases[0]["app_version"], "1.10.3");
+        assert_eq!(run(&args("ls -o json"), state_dir).unwrap(), "[]\n");
+
+        run(&args("uninstall whoami -n apps --wait"), state_dir).unwrap();
+
+        assert!(run(&args("uninstall whoami -n apps"), state_dir)
+            .unwrap_err()
+            .contains("release: not found"));
+    }
+
+    #[test]
+    fn lock_file_left_by_killed_process_is_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("state.lock"), "").unwrap();
+
+        assert_eq!(run(&args("ls -o json"), dir.path()).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn values_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_dir = dir.path().join("state");
+
+        run(
+            &args("repo add cowboysysop https://cowboysysop.github.io/charts/"),
+            &state_dir,
+        )
+        .unwrap();
+
+        let invalid = Path::new("test-data").join("whoami-invalid-syntax.yml");
+        let error = run(
+            &args(&format!(
+                "upgrade --install whoami cowboysysop/whoami -f {}",
+                invalid.display()
+            )),
+            &state_dir,
+        )
+        .unwrap_err();
+
+        assert!(error.contains("error converting YAML to JSON"));
+
+        let programmed = dir.path().join("values.yml");
+        std::fs::write(
+            &programmed,
+            "replicaCount: 1\nfakeHelmError: \"timed out waiting for the condition\"\n",
+        )
+        .unwrap();
+
+        let error = run(
+            &args(&format!(
+                "upgrade --install whoami cowboysysop/whoami -f {}",
+                programmed.display()
+            )),
+            &state_dir,
+        )
+        .unwrap_err();
+
+        assert_eq!(error, "timed out waiting for the condition");
+        assert_eq!(run(&args("ls -o json"), &state_dir).unwrap(), "[]\n");
+    }
+}
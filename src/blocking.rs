@@ -1,9 +1,22 @@
-use std::{collections::HashMap, path::Path, process::Command};
+use std::{ffi::OsString, path::Path};
 
 use log::{debug, error, info};
 use non_blank_string_rs::NonBlankString;
 
-use crate::{error::HelmWrapperError, HelmDeployStatus, HelmListItem, HelmUpgradeResponse};
+use crate::{
+    command::{self, GetSubject, HelmCommand, HelmSettings},
+    error::HelmWrapperError,
+    lint::LintReport,
+    manifest::{parse_manifest, ManifestDocument},
+    redact::Redactor,
+    registry::{parse_pushed_chart, PushedChart, RegistryLoginOptions},
+    repo::{HelmRepository, RepoAddOptions, RepoCredentials},
+    request::{validate_values, InstallOrUpgradeRequest, ValuesSource},
+    runner::{CommandRunner, SystemCommandRunner},
+    search::{self, ChartSearchResult},
+    HelmDeployStatus, HelmListItem, HelmRelease, HelmReleaseMetadata, HelmReleaseStatus,
+    HelmRevision, RollbackOptions,
+};
 
 pub trait HelmExecutor {
     /// List installed helm charts
@@ -19,19 +32,67 @@ pub trait HelmExecutor {
     /// - `release_name` - release name. For example: myapp
     /// - `chart_name` - helm chart name. For example: cowboysysop/whoami
     /// - `chart_version` - helm chart version. For example: 1.2.3 (optional)
-    /// - `values_overrides` - values overrides, pass to helm as --set NAME=VALUE (optional)
+    /// - `values_overrides` - values overrides, pass to helm as --set NAME=VALUE in the given order (optional)
     /// - `values-file` - path to values file (optional)
     /// - `helm_options` - any other options for helm. for example '--dry-run' (optional)
+    ///
+    /// Shortcut for `install_or_upgrade_with`, use `InstallOrUpgradeRequest` for other options.
+    #[allow(clippy::too_many_arguments)]
     fn install_or_upgrade(
         &self,
         namespace: &NonBlankString,
         release_name: &NonBlankString,
         chart_name: &NonBlankString,
         chart_version: Option<&NonBlankString>,
-        values_overrides: Option<&HashMap<NonBlankString, String>>,
+        values_overrides: Option<&[(NonBlankString, String)]>,
         values_file: Option<&Path>,
         helm_options: Option<&Vec<NonBlankString>>,
-    ) -> Result<HelmDeployStatus, HelmWrapperError>;
+    ) -> Result<HelmDeployStatus, HelmWrapperError> {
+        let release = self.install_or_upgrade_release(
+            namespace,
+            release_name,
+            chart_name,
+            chart_version,
+            values_overrides,
+            values_file,
+            helm_options,
+        )?;
+
+        Ok(release.info.status)
+    }
+
+    /// Same as `install_or_upgrade`, but returns the whole release object: revision,
+    /// rendered manifest, notes, chart metadata and values.
+    #[allow(clippy::too_many_arguments)]
+    fn install_or_upgrade_release(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        chart_name: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        values_overrides: Option<&[(NonBlankString, String)]>,
+        values_file: Option<&Path>,
+        helm_options: Option<&Vec<NonBlankString>>,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        self.install_or_upgrade_with(&InstallOrUpgradeRequest::from_args(
+            namespace,
+            release_name,
+            chart_name,
+            chart_version,
+            values_overrides,
+            values_file,
+            helm_options,
+        ))
+    }
+
+    /// Install or upgrade helm chart with all options from request:
+    /// helm upgrade --install <RELEASE-NAME> <CHART-NAME> [OPTIONS]
+    ///
+    /// Returns the whole release object.
+    fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError>;
 
     /// - `helm_options` - any other options for helm. for example '--dry-run' (optional)
     fn uninstall(
@@ -39,10 +100,235 @@ pub trait HelmExecutor {
         namespace: &NonBlankString,
         release_name: &NonBlankString,
     ) -> Result<(), HelmWrapperError>;
+
+    /// Rollback release in such way:
+    /// helm rollback <RELEASE-NAME> <REVISION> [--wait] [--cleanup-on-fail] [--force] [--recreate-pods]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - target revision, previous revision if not specified (optional)
+    /// - `options` - rollback options
+    ///
+    /// Returns revision which release was rolled back to.
+    fn rollback(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError>;
+
+    /// Get release history in such way:
+    /// helm history <RELEASE-NAME> [--max MAX]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `max` - maximum number of revisions to return (optional)
+    fn history(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError>;
+
+    /// Get release status in such way:
+    /// helm status <RELEASE-NAME> [--revision REVISION] --show-resources
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn status(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError>;
+
+    /// Get release values in such way:
+    /// helm get values <RELEASE-NAME> [--revision REVISION] [--all]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    /// - `all` - return all computed values instead of user supplied ones
+    fn get_values(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError>;
+
+    /// Get release manifest split into Kubernetes documents:
+    /// helm get manifest <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_manifest(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError>;
+
+    /// Get release notes:
+    /// helm get notes <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_notes(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError>;
+
+    /// Get release hooks split into Kubernetes documents:
+    /// helm get hooks <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_hooks(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError>;
+
+    /// Get release metadata:
+    /// helm get metadata <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_metadata(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError>;
+
+    /// Render chart templates locally, without cluster:
+    /// helm template <RELEASE-NAME> <CHART-NAME> [OPTIONS]
+    ///
+    /// Namespace, release and chart names, chart version, values, overrides and helm options
+    /// are taken from request, install options (atomic, wait, labels etc.) are ignored.
+    /// Returns rendered documents.
+    fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError>;
+
+    /// Lint chart:
+    /// helm lint <CHART-PATH> [-f VALUES]... [--strict]
+    /// - `chart_path` - path to chart directory or archive
+    /// - `values` - values sources in precedence order
+    /// - `strict` - fail on warnings
+    ///
+    /// Failed lint is not an error, check `LintReport::is_success`.
+    fn lint(
+        &self,
+        chart_path: &Path,
+        values: &[ValuesSource],
+        strict: bool,
+    ) -> Result<LintReport, HelmWrapperError>;
+
+    /// Add chart repository:
+    /// helm repo add <NAME> <URL> [OPTIONS]
+    /// - `name` - repository name. For example: cowboysysop
+    /// - `url` - repository url
+    /// - `options` - credentials, CA file, TLS and update options
+    ///
+    /// Password is passed through stdin and masked in logs and errors.
+    fn repo_add(
+        &self,
+        name: &NonBlankString,
+        url: &NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError>;
+
+    /// Update information of available charts:
+    /// helm repo update [NAME]...
+    /// - `names` - repositories to update, all if empty
+    fn repo_update(&self, names: &[NonBlankString]) -> Result<(), HelmWrapperError>;
+
+    /// List chart repositories, empty if there are no repositories
+    fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError>;
+
+    /// Remove chart repository:
+    /// helm repo remove <NAME>
+    fn repo_remove(&self, name: &NonBlankString) -> Result<(), HelmWrapperError>;
+
+    /// Search charts in added repositories:
+    /// helm search repo <KEYWORD> [--versions] [--devel] [--version CONSTRAINT]
+    /// - `keyword` - chart name or part of it. For example: whoami
+    /// - `versions` - all versions of each chart, otherwise the latest one
+    /// - `devel` - include development versions (alpha, beta, release candidates)
+    /// - `version_constraint` - semver constraint. For example: ^5.0.0 (optional)
+    fn search_repo(
+        &self,
+        keyword: &NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError>;
+
+    /// Stable versions of release chart newer than the deployed one, newest first.
+    /// Searches all added repositories, so the same version may be found in several of them.
+    /// Empty if release chart version can't be determined.
+    fn newer_chart_versions(
+        &self,
+        release: &HelmListItem,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        let Some(keyword) = search::chart_keyword(release) else {
+            return Ok(vec![]);
+        };
+
+        let results = self.search_repo(&keyword, true, false, None)?;
+
+        Ok(search::newer_versions(release, &results))
+    }
+
+    /// Log in to OCI registry:
+    /// helm registry login <HOST> --username USERNAME --password-stdin [OPTIONS]
+    /// - `host` - registry host. For example: ghcr.io
+    /// - `credentials` - username and password, password is passed through stdin
+    /// - `options` - TLS options
+    fn registry_login(
+        &self,
+        host: &NonBlankString,
+        credentials: &RepoCredentials,
+        options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError>;
+
+    /// Log out from OCI registry:
+    /// helm registry logout <HOST>
+    fn registry_logout(&self, host: &NonBlankString) -> Result<(), HelmWrapperError>;
+
+    /// Push packaged chart to OCI registry:
+    /// helm push <CHART-PACKAGE> <REMOTE>
+    /// - `chart_package` - chart archive. For example: whoami-5.2.0.tgz
+    /// - `remote` - registry path. For example: oci://ghcr.io/charts
+    fn push(
+        &self,
+        chart_package: &Path,
+        remote: &NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError>;
+
+    /// Download chart archive to directory:
+    /// helm pull <CHART> [--version VERSION] --destination <DIR>
+    /// - `chart` - chart reference. For example: cowboysysop/whoami or oci://ghcr.io/charts/whoami
+    /// - `chart_version` - chart version, the latest if not specified (optional)
+    /// - `destination` - target directory
+    fn pull(
+        &self,
+        chart: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        destination: &Path,
+    ) -> Result<(), HelmWrapperError>;
 }
 
+/// Helm executor, runs helm processes with `R` runner.
+///
+/// `SystemCommandRunner` spawns real helm, use `with_runner` to replace it,
+/// e.g. with `ScriptedCommandRunner` in unit tests.
 #[derive(Clone, Debug)]
-pub struct DefaultHelmExecutor(String, Option<String>, u16, bool, bool);
+pub struct DefaultHelmExecutor<R = SystemCommandRunner>(HelmSettings, R);
 
 impl DefaultHelmExecutor {
     /// Create executor instance with predefined option values:
@@ -52,7 +338,7 @@ impl DefaultHelmExecutor {
     /// - Debug: false
     /// - unsafe_mode: false - print overridden values to log
     pub fn new() -> Self {
-        Self("helm".to_string(), None, 15, false, false)
+        Self(HelmSettings::default(), SystemCommandRunner)
     }
 
     /// Create execute with options:
@@ -69,313 +355,626 @@ impl DefaultHelmExecutor {
         unsafe_mode: bool,
     ) -> Self {
         Self(
-            helm_path.to_string(),
-            kubeconfig_path,
-            timeout,
-            debug,
-            unsafe_mode,
+            HelmSettings {
+                helm_path: helm_path.to_string(),
+                kubeconfig_path,
+                timeout,
+                debug,
+                unsafe_mode,
+                redactor: Redactor::default(),
+                env: vec![],
+            },
+            SystemCommandRunner,
         )
     }
+}
+
+impl<R> DefaultHelmExecutor<R> {
+    /// Replace process runner, settings are kept
+    pub fn with_runner<T>(self, runner: T) -> DefaultHelmExecutor<T> {
+        DefaultHelmExecutor(self.0, runner)
+    }
+
+    pub fn get_runner(&self) -> &R {
+        &self.1
+    }
+
+    /// Set redactor which scrubs sensitive values from logs and error payloads:
+    /// command args, helm output and `HelmCommandFailure` stdout/stderr
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.0.redactor = redactor;
+        self
+    }
+
+    /// Set environment variable for helm process in addition to inherited ones,
+    /// e.g. `HELM_REPOSITORY_CONFIG` or `HELM_CACHE_HOME` to isolate helm state
+    pub fn with_env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.0.env.push((key.into(), value.into()));
+        self
+    }
 
     pub fn get_helm_path(&self) -> &str {
-        &self.0
+        &self.0.helm_path
     }
 
     pub fn get_kubeconfig_path(&self) -> &Option<String> {
-        &self.1
+        &self.0.kubeconfig_path
     }
 
     pub fn get_timeout(&self) -> u16 {
-        self.2
+        self.0.timeout
     }
 
     pub fn get_debug(&self) -> bool {
-        self.3
+        self.0.debug
     }
 
     pub fn get_unsafe_mode(&self) -> bool {
-        self.4
+        self.0.unsafe_mode
+    }
+
+    pub fn get_redactor(&self) -> &Redactor {
+        &self.0.redactor
+    }
+}
+
+impl<R: CommandRunner> DefaultHelmExecutor<R> {
+    /// Run helm with given arguments and return its stdout
+    fn execute(&self, command: HelmCommand) -> Result<String, HelmWrapperError> {
+        if self.get_unsafe_mode() {
+            debug!(
+                "command args: '{}'",
+                command.args.redacted(&self.0.redactor)
+            )
+        }
+
+        match self.1.run(&command.invocation(&self.0)) {
+            Ok(output) => command.read_output(&self.0, output),
+            Err(e) => {
+                error!("helm execution error: {}", e);
+                Err(HelmWrapperError::ExecutionError(e))
+            }
+        }
     }
+}
 
-    fn remove_double_spaces_and_trim(&self, input: &str) -> String {
-        let result = input.replace("  ", " ");
-        result.trim().to_string()
+impl Default for DefaultHelmExecutor {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl HelmExecutor for DefaultHelmExecutor {
+impl<R: CommandRunner> HelmExecutor for DefaultHelmExecutor<R> {
     fn list(
         &self,
         namespace: Option<&NonBlankString>,
     ) -> Result<Vec<HelmListItem>, HelmWrapperError> {
         info!("get list of installed helm charts..");
 
-        debug!("helm executable path '{}'", self.get_helm_path());
-        debug!("kubeconfig file path '{:?}'", self.get_kubeconfig_path());
-        debug!("timeout {}s", self.get_timeout());
+        self.0.log();
 
-        let mut command_args = format!("ls");
+        let stdout = self.execute(command::list_command(&self.0, namespace))?;
 
-        match &self.1 {
-            Some(kubeconfig_path) => {
-                info!("- kubeconfig path '{}'", kubeconfig_path);
-                command_args.push_str(&format!(" --kubeconfig={} ", kubeconfig_path));
-            }
-            None => {
-                debug!("no kubeconfig path provided");
-            }
-        }
+        let helm_response: Vec<HelmListItem> = serde_json::from_str(&stdout)?;
 
-        if let Some(namespace) = namespace {
-            info!("- namespace '{namespace}'");
-            command_args.push_str(&format!(" -n {} -o json ", namespace));
-        }
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
+        );
 
-        if self.get_debug() {
-            command_args.push_str(" --debug ");
-        }
+        Ok(helm_response)
+    }
 
-        command_args = self.remove_double_spaces_and_trim(&command_args);
+    fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        info!(
+            "installing helm chart '{}' with release name '{}' to namespace '{}'..",
+            request.chart_name, request.release_name, request.namespace
+        );
 
-        let command_args: Vec<&str> = command_args.split(" ").collect();
+        self.0.log();
 
-        match Command::new(&self.get_helm_path())
-            .args(command_args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8(output.stdout)?;
+        request.validate()?;
 
-                    if self.get_unsafe_mode() {
-                        debug!("<stdout>");
-                        debug!("{}", stdout);
-                        debug!("</stdout>");
-                    }
+        let stdout = self.execute(command::install_or_upgrade_command(&self.0, request)?)?;
 
-                    let helm_response: Vec<HelmListItem> = serde_json::from_str(&stdout)?;
+        let helm_response: HelmRelease = serde_json::from_str(&stdout)?;
 
-                    info!("response: {:?}", helm_response);
+        info!(
+            "release '{}' revision {} status '{}'",
+            helm_response.name, helm_response.version, helm_response.info.status
+        );
 
-                    Ok(helm_response)
-                } else {
-                    error!("helm command execution error");
-                    let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(helm_response)
+    }
 
-                    error!("<stderr>");
-                    error!("{}", stderr);
-                    error!("</stderr>");
+    fn uninstall(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        info!(
+            "uninstalling helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-                    Err(HelmWrapperError::Error)
-                }
-            }
-            Err(e) => {
-                error!("helm execution error: {}", e);
-                Err(HelmWrapperError::ExecutionError(e))
+        self.execute(command::uninstall_command(&self.0, namespace, release_name))?;
+
+        info!("helm release '{}' uninstalled successfully", release_name);
+
+        Ok(())
+    }
+
+    fn rollback(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        info!(
+            "rolling back helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
+
+        self.0.log();
+
+        let revision = match revision {
+            Some(revision) => revision,
+            None => {
+                let stdout = self.execute(command::release_list_command(
+                    &self.0,
+                    namespace,
+                    release_name,
+                ))?;
+
+                let releases: Vec<HelmListItem> = serde_json::from_str(&stdout)?;
+
+                command::previous_revision(release_name, &releases)?
             }
-        }
+        };
+
+        info!("- revision {revision}");
+
+        self.execute(command::rollback_command(
+            &self.0,
+            namespace,
+            release_name,
+            revision,
+            options,
+        ))?;
+
+        info!(
+            "helm release '{}' rolled back to revision {}",
+            release_name, revision
+        );
+
+        Ok(revision)
     }
 
-    fn install_or_upgrade(
+    fn history(
         &self,
         namespace: &NonBlankString,
         release_name: &NonBlankString,
-        chart_name: &NonBlankString,
-        chart_version: Option<&NonBlankString>,
-        values_overrides: Option<&HashMap<NonBlankString, String>>,
-        values_file: Option<&Path>,
-        helm_options: Option<&Vec<NonBlankString>>,
-    ) -> Result<HelmDeployStatus, HelmWrapperError> {
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
         info!(
-            "installing helm chart '{}' with release name '{}' to namespace '{}'..",
-            chart_name, release_name, namespace
+            "get history of helm release '{}', namespace '{}'..",
+            release_name, namespace
         );
 
-        debug!("helm executable path '{}'", self.get_helm_path());
-        debug!("kubeconfig file path '{:?}'", self.get_kubeconfig_path());
-        debug!("timeout {}s", self.get_timeout());
+        self.0.log();
 
-        let mut command_args = format!(
-            "upgrade --install -n {} {} {}",
-            namespace, release_name, chart_name
+        let stdout = self.execute(command::history_command(
+            &self.0,
+            namespace,
+            release_name,
+            max,
+        ))?;
+
+        let helm_response: Vec<HelmRevision> = serde_json::from_str(&stdout)?;
+
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
         );
 
-        match &self.1 {
-            Some(kubeconfig_path) => {
-                info!("- kubeconfig path '{}'", kubeconfig_path);
-                command_args.push_str(&format!(" --kubeconfig={} ", kubeconfig_path));
-            }
-            None => {
-                debug!("no kubeconfig path provided");
-            }
-        }
+        Ok(helm_response)
+    }
 
-        if let Some(chart_version) = chart_version {
-            info!("- chart version '{chart_version}'");
-            command_args.push_str(&format!(" --version {} ", chart_version));
-        }
+    fn status(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        info!(
+            "get status of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-        if let Some(values_file) = values_file {
-            info!("- values file '{}'", values_file.display());
-            command_args.push_str(&format!(" -f {} ", values_file.display()));
-        }
+        self.0.log();
 
-        if let Some(overrides) = values_overrides {
-            if !self.get_unsafe_mode() {
-                info!("overriden chart values won't be mentioned in log because of safe mode");
-            }
+        let stdout = self.execute(command::status_command(
+            &self.0,
+            namespace,
+            release_name,
+            revision,
+        ))?;
 
-            for (k, v) in overrides.iter() {
-                if self.get_unsafe_mode() {
-                    info!("- value override '{}': '{}'", k, v);
-                }
-                command_args.push_str(&format!(" --set {}={} ", k, v));
-            }
-        }
+        let helm_response: HelmReleaseStatus = serde_json::from_str(&stdout)?;
 
-        if let Some(helm_options) = helm_options {
-            for helm_option in helm_options {
-                info!("- helm option '{helm_option}'");
-                command_args.push_str(&format!(" {helm_option} "));
-            }
-        }
+        info!(
+            "release '{}' revision {} status '{}'",
+            helm_response.name, helm_response.revision, helm_response.info.status
+        );
 
-        if self.get_debug() {
-            command_args.push_str(" --debug ");
-        }
+        Ok(helm_response)
+    }
 
-        command_args.push_str(&format!(" -o json --timeout={}s ", self.get_timeout()));
+    fn get_values(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        info!(
+            "get values of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-        let command_args = command_args.replace("  ", " ");
-        let command_args = command_args.trim();
+        self.0.log();
 
-        if self.get_unsafe_mode() {
-            debug!("command args: '{command_args}'")
-        }
+        let stdout = self.execute(command::get_command(
+            &self.0,
+            GetSubject::Values { all },
+            namespace,
+            release_name,
+            revision,
+        ))?;
 
-        let command_args: Vec<&str> = command_args.split(" ").collect();
+        let helm_response: serde_json::Value = serde_json::from_str(&stdout)?;
 
-        match Command::new(&self.get_helm_path())
-            .args(command_args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8(output.stdout)?;
+        Ok(helm_response)
+    }
 
-                    if self.get_unsafe_mode() {
-                        debug!("<stdout>");
-                        debug!("{}", stdout);
-                        debug!("</stdout>");
-                    }
+    fn get_manifest(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        info!(
+            "get manifest of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-                    let helm_response: HelmUpgradeResponse = serde_json::from_str(&stdout)?;
+        self.0.log();
 
-                    info!("response: {:?}", helm_response);
+        let stdout = self.execute(command::get_command(
+            &self.0,
+            GetSubject::Manifest,
+            namespace,
+            release_name,
+            revision,
+        ))?;
 
-                    Ok(helm_response.info.status)
-                } else {
-                    error!("helm command execution error");
-                    let stderr = String::from_utf8_lossy(&output.stderr);
+        let helm_response = parse_manifest(&stdout);
 
-                    error!("<stderr>");
-                    error!("{}", stderr);
-                    error!("</stderr>");
+        Ok(helm_response)
+    }
 
-                    Err(HelmWrapperError::Error)
-                }
-            }
-            Err(e) => {
-                error!("helm execution error: {}", e);
-                Err(HelmWrapperError::ExecutionError(e))
-            }
-        }
+    fn get_notes(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        info!(
+            "get notes of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
+
+        self.0.log();
+
+        let stdout = self.execute(command::get_command(
+            &self.0,
+            GetSubject::Notes,
+            namespace,
+            release_name,
+            revision,
+        ))?;
+
+        let helm_response = command::parse_notes(&stdout);
+
+        Ok(helm_response)
     }
 
-    fn uninstall(
+    fn get_hooks(
         &self,
         namespace: &NonBlankString,
         release_name: &NonBlankString,
-    ) -> Result<(), HelmWrapperError> {
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
         info!(
-            "uninstalling helm release '{}', namespace '{}'..",
+            "get hooks of helm release '{}', namespace '{}'..",
             release_name, namespace
         );
 
-        let mut command_args = format!(
-            "uninstall {} -n {} --timeout={}s --wait",
+        self.0.log();
+
+        let stdout = self.execute(command::get_command(
+            &self.0,
+            GetSubject::Hooks,
+            namespace,
             release_name,
+            revision,
+        ))?;
+
+        let helm_response = parse_manifest(&stdout);
+
+        Ok(helm_response)
+    }
+
+    fn get_metadata(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        info!(
+            "get metadata of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
+
+        self.0.log();
+
+        let stdout = self.execute(command::get_command(
+            &self.0,
+            GetSubject::Metadata,
             namespace,
-            self.get_timeout()
+            release_name,
+            revision,
+        ))?;
+
+        let helm_response: HelmReleaseMetadata = serde_json::from_str(&stdout)?;
+
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
         );
 
-        if self.get_debug() {
-            command_args.push_str(" --debug ");
-        }
+        Ok(helm_response)
+    }
 
-        match &self.1 {
-            Some(kubeconfig_path) => {
-                info!("- kubeconfig path '{}'", kubeconfig_path);
-                command_args.push_str(&format!(" --kubeconfig={} ", kubeconfig_path));
-            }
-            None => {
-                debug!("no kubeconfig path provided");
-            }
-        }
+    fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        info!(
+            "rendering helm chart '{}' with release name '{}', namespace '{}'..",
+            request.chart_name, request.release_name, request.namespace
+        );
 
-        if self.get_unsafe_mode() {
-            debug!("command args: '{command_args}'")
-        }
+        self.0.log();
 
-        let command_args: Vec<&str> = command_args.trim().split(" ").collect();
+        request.validate()?;
 
-        match Command::new(&self.get_helm_path())
-            .args(command_args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8(output.stdout)?;
+        let stdout = self.execute(command::template_command(&self.0, request)?)?;
 
-                    if self.get_unsafe_mode() {
-                        debug!("<stdout>");
-                        debug!("{}", stdout);
-                        debug!("</stdout>");
-                    }
+        let helm_response = parse_manifest(&stdout);
 
-                    info!("helm release '{}' uninstalled successfully", release_name);
+        info!("{} documents rendered", helm_response.len());
 
-                    Ok(())
-                } else {
-                    error!("helmcommand execution error");
-                    let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(helm_response)
+    }
 
-                    error!("<stderr>");
-                    error!("{}", stderr);
-                    error!("</stderr>");
+    fn lint(
+        &self,
+        chart_path: &Path,
+        values: &[ValuesSource],
+        strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        info!("linting helm chart '{}'..", chart_path.display());
 
-                    Err(HelmWrapperError::Error)
-                }
-            }
-            Err(e) => {
-                error!("helm execution error: {}", e);
-                Err(HelmWrapperError::ExecutionError(e))
-            }
-        }
+        self.0.log();
+
+        validate_values(values)?;
+
+        let result = self.execute(command::lint_command(&self.0, chart_path, values, strict)?);
+
+        let helm_response = command::read_lint_report(result)?;
+
+        info!(
+            "{} chart(s) linted, {} chart(s) failed",
+            helm_response.linted, helm_response.failed
+        );
+
+        Ok(helm_response)
+    }
+
+    fn repo_add(
+        &self,
+        name: &NonBlankString,
+        url: &NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        info!(
+            "adding helm repository '{}', url '{}'..",
+            name,
+            self.0.redactor.redact_text(url)
+        );
+
+        self.0.log();
+
+        self.execute(command::repo_add_command(&self.0, name, url, options))?;
+
+        info!("helm repository '{}' added", name);
+
+        Ok(())
+    }
+
+    fn repo_update(&self, names: &[NonBlankString]) -> Result<(), HelmWrapperError> {
+        info!("updating helm repositories..");
+
+        self.0.log();
+
+        self.execute(command::repo_update_command(&self.0, names))?;
+
+        info!("helm repositories updated");
+
+        Ok(())
+    }
+
+    fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        info!("get list of helm repositories..");
+
+        self.0.log();
+
+        let result = self.execute(command::repo_list_command(&self.0));
+
+        let helm_response = command::read_repo_list(result)?;
+
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
+        );
+
+        Ok(helm_response)
+    }
+
+    fn repo_remove(&self, name: &NonBlankString) -> Result<(), HelmWrapperError> {
+        info!("removing helm repository '{}'..", name);
+
+        self.0.log();
+
+        self.execute(command::repo_remove_command(&self.0, name))?;
+
+        info!("helm repository '{}' removed", name);
+
+        Ok(())
+    }
+
+    fn search_repo(
+        &self,
+        keyword: &NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        info!("searching helm charts by keyword '{}'..", keyword);
+
+        self.0.log();
+
+        let stdout = self.execute(command::search_repo_command(
+            &self.0,
+            keyword,
+            versions,
+            devel,
+            version_constraint,
+        ))?;
+
+        let helm_response: Vec<ChartSearchResult> = serde_json::from_str(&stdout)?;
+
+        info!("{} chart version(s) found", helm_response.len());
+
+        Ok(helm_response)
+    }
+
+    fn registry_login(
+        &self,
+        host: &NonBlankString,
+        credentials: &RepoCredentials,
+        options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        info!("logging in to registry '{}'..", host);
+
+        self.0.log();
+
+        self.execute(command::registry_login_command(
+            &self.0,
+            host,
+            credentials,
+            options,
+        ))?;
+
+        info!("logged in to registry '{}'", host);
+
+        Ok(())
+    }
+
+    fn registry_logout(&self, host: &NonBlankString) -> Result<(), HelmWrapperError> {
+        info!("logging out from registry '{}'..", host);
+
+        self.0.log();
+
+        self.execute(command::registry_logout_command(&self.0, host))?;
+
+        info!("logged out from registry '{}'", host);
+
+        Ok(())
+    }
+
+    fn push(
+        &self,
+        chart_package: &Path,
+        remote: &NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        info!(
+            "pushing chart '{}' to '{}'..",
+            chart_package.display(),
+            remote
+        );
+
+        self.0.log();
+
+        let stdout = self.execute(command::push_command(&self.0, chart_package, remote))?;
+
+        let helm_response = parse_pushed_chart(&stdout);
+
+        info!("response: {:?}", helm_response);
+
+        Ok(helm_response)
+    }
+
+    fn pull(
+        &self,
+        chart: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        destination: &Path,
+    ) -> Result<(), HelmWrapperError> {
+        info!("pulling chart '{}'..", chart);
+
+        self.0.log();
+
+        self.execute(command::pull_command(
+            &self.0,
+            chart,
+            chart_version,
+            destination,
+        ))?;
+
+        info!("chart '{}' pulled to '{}'", chart, destination.display());
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod blocking_helm_command_tests {
-    use std::{collections::HashMap, path::Path};
+    use std::path::Path;
 
     use non_blank_string_rs::NonBlankString;
 
     use crate::{
         blocking::{DefaultHelmExecutor, HelmExecutor},
+        repo::RepoAddOptions,
         tests::{
-            get_test_chart_name, get_test_helm_options, get_test_namespace, get_test_release_name,
-            init_logging,
+            get_test_chart_name, get_test_helm_options, get_test_helm_path, get_test_namespace,
+            get_test_release_name, get_test_repo_name, get_test_repo_url, init_logging,
         },
         HelmDeployStatus,
     };
@@ -385,7 +984,7 @@ mod blocking_helm_command_tests {
         init_logging();
 
         let executor =
-            DefaultHelmExecutor::new_with_opts(&"helm".parse().unwrap(), None, 15, true, true);
+            DefaultHelmExecutor::new_with_opts(&get_test_helm_path(), None, 15, true, true);
 
         let helm_options: Vec<NonBlankString> = get_test_helm_options();
 
@@ -413,18 +1012,29 @@ mod blocking_helm_command_tests {
         init_logging();
 
         let executor =
-            DefaultHelmExecutor::new_with_opts(&"helm".parse().unwrap(), None, 15, true, true);
+            DefaultHelmExecutor::new_with_opts(&get_test_helm_path(), None, 15, true, true);
+
+        executor
+            .repo_add(
+                &get_test_repo_name(),
+                &get_test_repo_url(),
+                &RepoAddOptions {
+                    force_update: true,
+                    ..RepoAddOptions::default()
+                },
+            )
+            .unwrap();
+        executor.repo_update(&[get_test_repo_name()]).unwrap();
 
         let helm_options: Vec<NonBlankString> = get_test_helm_options();
 
         let namespace: NonBlankString = get_test_namespace();
         let release_name: NonBlankString = get_test_release_name();
         let chart_name: NonBlankString = get_test_chart_name();
-
-        let mut values_overrides: HashMap<NonBlankString, String> = HashMap::new();
-
-        values_overrides.insert("startupProbe.enabled".parse().unwrap(), "false".to_string());
-        values_overrides.insert("replicaCount".parse().unwrap(), "2".to_string());
+        let values_overrides: Vec<(NonBlankString, String)> = vec![
+            ("startupProbe.enabled".parse().unwrap(), "false".to_string()),
+            ("replicaCount".parse().unwrap(), "2".to_string()),
+        ];
 
         let values_file = Path::new("test-data").join("whoami-values.yml");
 
@@ -434,7 +1044,7 @@ mod blocking_helm_command_tests {
                 &release_name,
                 &chart_name,
                 Some(&"5.2.0".parse().unwrap()),
-                Some(&values_overrides),
+                Some(values_overrides.as_slice()),
                 Some(&values_file),
                 Some(&helm_options),
             )
@@ -460,3 +1070,149 @@ mod blocking_helm_command_tests {
         assert!(releases.is_empty());
     }
 }
+
+#[cfg(test)]
+mod blocking_executor_tests {
+    use std::io;
+
+    use crate::{
+        blocking::{DefaultHelmExecutor, HelmExecutor},
+        error::HelmWrapperError,
+        repo::{RepoAddOptions, RepoCredentials},
+        request::InstallOrUpgradeRequest,
+        runner::{CommandOutput, ScriptedCommandRunner},
+        HelmDeployStatus,
+    };
+
+    fn executor(runner: &ScriptedCommandRunner) -> DefaultHelmExecutor<ScriptedCommandRunner> {
+        DefaultHelmExecutor::new_with_opts(
+            &"/usr/bin/helm".parse().unwrap(),
+            None,
+            15,
+            false,
+            false,
+        )
+        .with_runner(runner.clone())
+    }
+
+    #[test]
+    fn list_is_parsed_from_runner_output() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::ok(
+            r#"[{"name":"whoami","namespace":"whoami","revision":"1","updated":"2024-01-01 00:00:00","status":"deployed","chart":"whoami-5.2.0","app_version":"1.10.3"}]"#,
+        ));
+
+        let releases = executor(&runner)
+            .list(Some(&"whoami".parse().unwrap()))
+            .unwrap();
+
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].status, HelmDeployStatus::Deployed);
+        assert_eq!(releases[0].app_version, "1.10.3");
+
+        let invocations = runner.invocations();
+
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].program, "/usr/bin/helm");
+        assert_eq!(
+            invocations[0].args_lossy(),
+            vec!["ls", "-n", "whoami", "-o", "json"]
+        );
+        assert_eq!(invocations[0].stdin, None);
+    }
+
+    #[test]
+    fn environment_is_passed_to_runner() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::ok("[]"));
+
+        executor(&runner)
+            .with_env("HELM_REPOSITORY_CONFIG", "/tmp/helm/repositories.yaml")
+            .list(None)
+            .unwrap();
+
+        assert_eq!(
+            runner.invocations()[0].env,
+            vec![(
+                "HELM_REPOSITORY_CONFIG".into(),
+                "/tmp/helm/repositories.yaml".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn invalid_label_fails_before_helm_is_invoked() {
+        let runner = ScriptedCommandRunner::new();
+
+        let request = InstallOrUpgradeRequest::new(
+            "whoami".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .label("owner", "a,team=b");
+
+        assert!(matches!(
+            executor(&runner).install_or_upgrade_with(&request),
+            Err(HelmWrapperError::InvalidLabel(_))
+        ));
+        assert!(runner.invocations().is_empty());
+    }
+
+    #[test]
+    fn failed_command_is_classified() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::failure(
+            1,
+            "Error: uninstall: Release not loaded: whoami: release: not found",
+        ));
+
+        let error = executor(&runner)
+            .uninstall(&"whoami".parse().unwrap(), &"whoami".parse().unwrap())
+            .unwrap_err();
+
+        assert!(matches!(error, HelmWrapperError::ReleaseNotFound(_)));
+
+        let failure = error.failure().unwrap();
+
+        assert_eq!(failure.subcommand, "uninstall");
+        assert_eq!(failure.release_name.as_deref(), Some("whoami"));
+        assert_eq!(failure.exit_code, Some(1));
+    }
+
+    #[test]
+    fn runner_error_is_execution_error() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_error(io::Error::new(io::ErrorKind::NotFound, "helm not found"));
+
+        let error = executor(&runner).list(None).unwrap_err();
+
+        assert!(matches!(error, HelmWrapperError::ExecutionError(_)));
+    }
+
+    #[test]
+    fn repo_password_is_written_to_stdin() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::ok("\"private\" has been added\n"));
+
+        let options = RepoAddOptions {
+            credentials: Some(RepoCredentials::new("admin", "p4ss")),
+            ..RepoAddOptions::default()
+        };
+
+        executor(&runner)
+            .repo_add(
+                &"private".parse().unwrap(),
+                &"https://charts.example.com".parse().unwrap(),
+                &options,
+            )
+            .unwrap();
+
+        let invocation = runner.invocations().remove(0);
+
+        assert_eq!(invocation.stdin.as_deref(), Some("p4ss"));
+        assert!(!invocation
+            .args_lossy()
+            .iter()
+            .any(|arg| arg.contains("p4ss")));
+    }
+}
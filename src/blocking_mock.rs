@@ -1,4 +1,17 @@
-use crate::{blocking::HelmExecutor, error::HelmWrapperError, HelmDeployStatus, HelmListItem};
+use crate::{
+    blocking::HelmExecutor,
+    error::HelmWrapperError,
+    lint::{ChartLintResult, LintReport},
+    manifest::ManifestDocument,
+    mock::{HelmCall, MockHelmExecutor},
+    registry::{PushedChart, RegistryLoginOptions},
+    repo::{HelmRepository, RepoAddOptions, RepoCredentials},
+    request::{InstallOrUpgradeRequest, ValuesSource},
+    search::ChartSearchResult,
+    simulator::SimulatorHelmExecutor,
+    HelmDeployStatus, HelmListItem, HelmRelease, HelmReleaseInfo, HelmReleaseMetadata,
+    HelmReleaseStatus, HelmRevision, RollbackOptions,
+};
 
 pub struct SuccessMockHelmExecutor(Vec<HelmListItem>, HelmDeployStatus);
 
@@ -19,26 +32,814 @@ impl HelmExecutor for SuccessMockHelmExecutor {
         Ok(self.0.clone())
     }
 
-    fn install_or_upgrade(
+    fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        Ok(HelmRelease {
+            name: request.release_name.to_string(),
+            namespace: request.namespace.to_string(),
+            version: 1,
+            info: HelmReleaseInfo {
+                status: self.1.clone(),
+                ..HelmReleaseInfo::default()
+            },
+            ..HelmRelease::default()
+        })
+    }
+
+    fn uninstall(
         &self,
         _namespace: &non_blank_string_rs::NonBlankString,
         _release_name: &non_blank_string_rs::NonBlankString,
-        _chart_name: &non_blank_string_rs::NonBlankString,
-        _chart_version: Option<&non_blank_string_rs::NonBlankString>,
-        _values_overrides: Option<
-            &std::collections::HashMap<non_blank_string_rs::NonBlankString, String>,
-        >,
-        _values_file: Option<&std::path::Path>,
-        _helm_options: Option<&Vec<non_blank_string_rs::NonBlankString>>,
-    ) -> Result<HelmDeployStatus, HelmWrapperError> {
-        Ok(self.1.clone())
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
     }
 
-    fn uninstall(
+    fn rollback(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        _options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        Ok(revision.unwrap_or(1))
+    }
+
+    fn history(
         &self,
         _namespace: &non_blank_string_rs::NonBlankString,
         _release_name: &non_blank_string_rs::NonBlankString,
+        _max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    fn status(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        Ok(HelmReleaseStatus {
+            name: release_name.to_string(),
+            namespace: namespace.to_string(),
+            revision: revision.unwrap_or(1),
+            info: HelmReleaseInfo {
+                status: self.1.clone(),
+                ..HelmReleaseInfo::default()
+            },
+            ..HelmReleaseStatus::default()
+        })
+    }
+
+    fn get_values(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+        _all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        Ok(serde_json::Value::Null)
+    }
+
+    fn get_manifest(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    fn get_notes(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        Ok(String::new())
+    }
+
+    fn get_hooks(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    fn get_metadata(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        Ok(HelmReleaseMetadata {
+            name: release_name.to_string(),
+            namespace: namespace.to_string(),
+            revision: revision.unwrap_or(1),
+            status: self.1.clone(),
+            ..HelmReleaseMetadata::default()
+        })
+    }
+
+    fn template(
+        &self,
+        _request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    fn lint(
+        &self,
+        chart_path: &std::path::Path,
+        _values: &[ValuesSource],
+        _strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        Ok(LintReport {
+            charts: vec![ChartLintResult {
+                chart: chart_path.display().to_string(),
+                findings: vec![],
+            }],
+            linted: 1,
+            failed: 0,
+        })
+    }
+
+    fn repo_add(
+        &self,
+        _name: &non_blank_string_rs::NonBlankString,
+        _url: &non_blank_string_rs::NonBlankString,
+        _options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn repo_update(
+        &self,
+        _names: &[non_blank_string_rs::NonBlankString],
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    fn repo_remove(
+        &self,
+        _name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn search_repo(
+        &self,
+        _keyword: &non_blank_string_rs::NonBlankString,
+        _versions: bool,
+        _devel: bool,
+        _version_constraint: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    fn registry_login(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+        _credentials: &RepoCredentials,
+        _options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn registry_logout(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn push(
+        &self,
+        _chart_package: &std::path::Path,
+        _remote: &non_blank_string_rs::NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        Ok(PushedChart::default())
+    }
+
+    fn pull(
+        &self,
+        _chart: &non_blank_string_rs::NonBlankString,
+        _chart_version: Option<&non_blank_string_rs::NonBlankString>,
+        _destination: &std::path::Path,
     ) -> Result<(), HelmWrapperError> {
         Ok(())
     }
 }
+
+impl HelmExecutor for SimulatorHelmExecutor {
+    fn list(
+        &self,
+        namespace: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<HelmListItem>, HelmWrapperError> {
+        Ok(self.state().list(namespace))
+    }
+
+    fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        self.state().install_or_upgrade(request)
+    }
+
+    fn uninstall(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        self.state().uninstall(namespace, release_name)
+    }
+
+    fn rollback(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        _options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        self.state().rollback(namespace, release_name, revision)
+    }
+
+    fn history(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        self.state().history(namespace, release_name, max)
+    }
+
+    fn status(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        self.state().status(namespace, release_name, revision)
+    }
+
+    fn get_values(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        _all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        self.state().get_values(namespace, release_name, revision)
+    }
+
+    fn get_manifest(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        self.state()
+            .ensure_revision(namespace, release_name, revision)
+            .map(|_| vec![])
+    }
+
+    fn get_notes(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        self.state()
+            .ensure_revision(namespace, release_name, revision)
+            .map(|_| String::new())
+    }
+
+    fn get_hooks(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        self.state()
+            .ensure_revision(namespace, release_name, revision)
+            .map(|_| vec![])
+    }
+
+    fn get_metadata(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        self.state().get_metadata(namespace, release_name, revision)
+    }
+
+    fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        request.validate()?;
+        Ok(vec![])
+    }
+
+    fn lint(
+        &self,
+        chart_path: &std::path::Path,
+        _values: &[ValuesSource],
+        _strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        Ok(LintReport {
+            charts: vec![ChartLintResult {
+                chart: chart_path.display().to_string(),
+                findings: vec![],
+            }],
+            linted: 1,
+            failed: 0,
+        })
+    }
+
+    fn repo_add(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+        url: &non_blank_string_rs::NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        self.state().repo_add(name, url, options)
+    }
+
+    fn repo_update(
+        &self,
+        names: &[non_blank_string_rs::NonBlankString],
+    ) -> Result<(), HelmWrapperError> {
+        self.state().repo_update(names)
+    }
+
+    fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        Ok(self.state().repo_list())
+    }
+
+    fn repo_remove(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        self.state().repo_remove(name)
+    }
+
+    fn search_repo(
+        &self,
+        keyword: &non_blank_string_rs::NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        self.state()
+            .search_repo(keyword, versions, devel, version_constraint)
+    }
+
+    fn registry_login(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+        _credentials: &RepoCredentials,
+        _options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn registry_logout(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    fn push(
+        &self,
+        _chart_package: &std::path::Path,
+        _remote: &non_blank_string_rs::NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        Ok(PushedChart::default())
+    }
+
+    fn pull(
+        &self,
+        _chart: &non_blank_string_rs::NonBlankString,
+        _chart_version: Option<&non_blank_string_rs::NonBlankString>,
+        _destination: &std::path::Path,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+}
+
+impl HelmExecutor for MockHelmExecutor {
+    fn list(
+        &self,
+        namespace: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<HelmListItem>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::List {
+                namespace: namespace.map(|namespace| namespace.to_string()),
+            },
+        )?;
+
+        self.simulator().list(namespace)
+    }
+
+    fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        let status = intercept(self, HelmCall::InstallOrUpgrade(request.clone()))?;
+
+        let mut release = self.simulator().install_or_upgrade_with(request)?;
+
+        if let Some(status) = status {
+            if request.dry_run.is_none() {
+                self.simulator().set_status(
+                    &request.namespace,
+                    &request.release_name,
+                    status.clone(),
+                )?;
+            }
+
+            release.info.status = status;
+        }
+
+        Ok(release)
+    }
+
+    fn uninstall(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Uninstall {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+            },
+        )?;
+
+        self.simulator().uninstall(namespace, release_name)
+    }
+
+    fn rollback(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        let status = intercept(
+            self,
+            HelmCall::Rollback {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+                options: options.clone(),
+            },
+        )?;
+
+        let revision = self
+            .simulator()
+            .rollback(namespace, release_name, revision, options)?;
+
+        if let Some(status) = status {
+            self.simulator()
+                .set_status(namespace, release_name, status)?;
+        }
+
+        Ok(revision)
+    }
+
+    fn history(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::History {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                max,
+            },
+        )?;
+
+        self.simulator().history(namespace, release_name, max)
+    }
+
+    fn status(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        let status = intercept(
+            self,
+            HelmCall::Status {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )?;
+
+        let mut release = self.simulator().status(namespace, release_name, revision)?;
+
+        if let Some(status) = status {
+            release.info.status = status;
+        }
+
+        Ok(release)
+    }
+
+    fn get_values(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetValues {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+                all,
+            },
+        )?;
+
+        self.simulator()
+            .get_values(namespace, release_name, revision, all)
+    }
+
+    fn get_manifest(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetManifest {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )?;
+
+        self.simulator()
+            .get_manifest(namespace, release_name, revision)
+    }
+
+    fn get_notes(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetNotes {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )?;
+
+        self.simulator()
+            .get_notes(namespace, release_name, revision)
+    }
+
+    fn get_hooks(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetHooks {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )?;
+
+        self.simulator()
+            .get_hooks(namespace, release_name, revision)
+    }
+
+    fn get_metadata(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        let status = intercept(
+            self,
+            HelmCall::GetMetadata {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )?;
+
+        let mut metadata = self
+            .simulator()
+            .get_metadata(namespace, release_name, revision)?;
+
+        if let Some(status) = status {
+            metadata.status = status;
+        }
+
+        Ok(metadata)
+    }
+
+    fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        intercept(self, HelmCall::Template(request.clone()))?;
+
+        self.simulator().template(request)
+    }
+
+    fn lint(
+        &self,
+        chart_path: &std::path::Path,
+        values: &[ValuesSource],
+        strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Lint {
+                chart_path: chart_path.to_path_buf(),
+                values: values.to_vec(),
+                strict,
+            },
+        )?;
+
+        self.simulator().lint(chart_path, values, strict)
+    }
+
+    fn repo_add(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+        url: &non_blank_string_rs::NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(self, HelmCall::repo_add(name, url, options))?;
+
+        self.simulator().repo_add(name, url, options)
+    }
+
+    fn repo_update(
+        &self,
+        names: &[non_blank_string_rs::NonBlankString],
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RepoUpdate {
+                names: names.iter().map(|name| name.to_string()).collect(),
+            },
+        )?;
+
+        self.simulator().repo_update(names)
+    }
+
+    fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        intercept(self, HelmCall::RepoList)?;
+
+        self.simulator().repo_list()
+    }
+
+    fn repo_remove(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RepoRemove {
+                name: name.to_string(),
+            },
+        )?;
+
+        self.simulator().repo_remove(name)
+    }
+
+    fn search_repo(
+        &self,
+        keyword: &non_blank_string_rs::NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::SearchRepo {
+                keyword: keyword.to_string(),
+                versions,
+                devel,
+                version_constraint: version_constraint.map(|constraint| constraint.to_string()),
+            },
+        )?;
+
+        self.simulator()
+            .search_repo(keyword, versions, devel, version_constraint)
+    }
+
+    fn registry_login(
+        &self,
+        host: &non_blank_string_rs::NonBlankString,
+        credentials: &RepoCredentials,
+        options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RegistryLogin {
+                host: host.to_string(),
+                credentials: credentials.into(),
+                options: options.clone(),
+            },
+        )?;
+
+        self.simulator().registry_login(host, credentials, options)
+    }
+
+    fn registry_logout(
+        &self,
+        host: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RegistryLogout {
+                host: host.to_string(),
+            },
+        )?;
+
+        self.simulator().registry_logout(host)
+    }
+
+    fn push(
+        &self,
+        chart_package: &std::path::Path,
+        remote: &non_blank_string_rs::NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Push {
+                chart_package: chart_package.to_path_buf(),
+                remote: remote.to_string(),
+            },
+        )?;
+
+        self.simulator().push(chart_package, remote)
+    }
+
+    fn pull(
+        &self,
+        chart: &non_blank_string_rs::NonBlankString,
+        chart_version: Option<&non_blank_string_rs::NonBlankString>,
+        destination: &std::path::Path,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Pull {
+                chart: chart.to_string(),
+                chart_version: chart_version.map(|version| version.to_string()),
+                destination: destination.to_path_buf(),
+            },
+        )?;
+
+        self.simulator().pull(chart, chart_version, destination)
+    }
+}
+
+/// Record call of mock, wait for programmed delay and return programmed error or status
+fn intercept(
+    executor: &MockHelmExecutor,
+    call: HelmCall,
+) -> Result<Option<HelmDeployStatus>, HelmWrapperError> {
+    let outcome = executor.intercept(call);
+
+    if let Some(delay) = outcome.delay {
+        std::thread::sleep(delay);
+    }
+
+    match outcome.error {
+        Some(error) => Err(error),
+        None => Ok(outcome.status),
+    }
+}
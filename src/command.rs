@@ -0,0 +1,1622 @@
+use std::{ffi::OsString, io::Write, path::Path};
+
+use log::{debug, error, info};
+use non_blank_string_rs::NonBlankString;
+use tempfile::NamedTempFile;
+
+use crate::{
+    args::HelmArgs,
+    error::{HelmCommandFailure, HelmWrapperError},
+    lint::{parse_lint_report, parse_summary, LintReport},
+    redact::Redactor,
+    registry::{ChartReference, RegistryLoginOptions},
+    repo::{HelmRepository, RepoAddOptions, RepoCredentials},
+    request::{DryRun, InstallOrUpgradeRequest, ValuesSource},
+    runner::{CommandInvocation, CommandOutput},
+    HelmListItem, RollbackOptions,
+};
+
+/// Options shared by blocking and nonblocking executors
+#[derive(Clone, Debug)]
+pub(crate) struct HelmSettings {
+    pub helm_path: String,
+    pub kubeconfig_path: Option<String>,
+    pub timeout: u16,
+    pub debug: bool,
+    pub unsafe_mode: bool,
+    /// Scrubs logs and error payloads
+    pub redactor: Redactor,
+    /// Environment variables for helm process, e.g. `HELM_REPOSITORY_CONFIG`
+    pub env: Vec<(OsString, OsString)>,
+}
+
+impl Default for HelmSettings {
+    fn default() -> Self {
+        Self {
+            helm_path: "helm".to_string(),
+            kubeconfig_path: None,
+            timeout: 15,
+            debug: false,
+            unsafe_mode: false,
+            redactor: Redactor::default(),
+            env: vec![],
+        }
+    }
+}
+
+impl HelmSettings {
+    pub fn log(&self) {
+        debug!("helm executable path '{}'", self.helm_path);
+        debug!("kubeconfig file path '{:?}'", self.kubeconfig_path);
+        debug!("timeout {}s", self.timeout);
+
+        for (key, _) in &self.env {
+            debug!("environment variable '{}'", key.to_string_lossy());
+        }
+    }
+
+    /// Append options which every helm command accepts: kubeconfig path and debug flag
+    fn append_global_args(&self, args: &mut HelmArgs) {
+        match &self.kubeconfig_path {
+            Some(kubeconfig_path) => {
+                info!("- kubeconfig path '{}'", kubeconfig_path);
+                args.option("--kubeconfig", kubeconfig_path);
+            }
+            None => {
+                debug!("no kubeconfig path provided");
+            }
+        }
+
+        args.flag("--debug", self.debug);
+    }
+
+    fn timeout_arg(&self) -> String {
+        format!("{}s", self.timeout)
+    }
+}
+
+/// Helm invocation with release context used for error reports
+pub(crate) struct HelmCommand {
+    pub args: HelmArgs,
+    pub release_name: Option<String>,
+    pub namespace: Option<String>,
+    /// Files referenced by args, removed when command is dropped
+    pub temp_files: Vec<NamedTempFile>,
+    /// Secret input for helm, e.g. `--password-stdin`, masked in logs and errors
+    pub stdin: Option<String>,
+}
+
+impl HelmCommand {
+    fn new(
+        args: HelmArgs,
+        namespace: Option<&NonBlankString>,
+        release_name: Option<&NonBlankString>,
+    ) -> Self {
+        Self {
+            args,
+            release_name: release_name.map(|release_name| release_name.to_string()),
+            namespace: namespace.map(|namespace| namespace.to_string()),
+            temp_files: vec![],
+            stdin: None,
+        }
+    }
+
+    /// Settings redactor which also masks stdin content
+    fn redactor(&self, settings: &HelmSettings) -> Redactor {
+        match &self.stdin {
+            Some(stdin) => settings.redactor.clone().secret(stdin.trim_end()),
+            None => settings.redactor.clone(),
+        }
+    }
+
+    /// Helm process to run with given settings
+    pub fn invocation(&self, settings: &HelmSettings) -> CommandInvocation {
+        CommandInvocation {
+            program: settings.helm_path.clone(),
+            args: self.args.iter().cloned().collect(),
+            env: settings.env.clone(),
+            stdin: self.stdin.clone(),
+        }
+    }
+
+    /// Get stdout of finished helm process or error with stderr logged
+    pub fn read_output(
+        self,
+        settings: &HelmSettings,
+        output: CommandOutput,
+    ) -> Result<String, HelmWrapperError> {
+        let redactor = self.redactor(settings);
+
+        if output.success() {
+            let stdout = String::from_utf8(output.stdout)?;
+
+            if settings.unsafe_mode {
+                debug!("<stdout>");
+                debug!("{}", redactor.redact_text(&stdout));
+                debug!("</stdout>");
+            }
+
+            Ok(stdout)
+        } else {
+            error!("helm command execution error");
+            let stderr = redactor.redact_text(&String::from_utf8_lossy(&output.stderr));
+
+            error!("<stderr>");
+            error!("{}", stderr);
+            error!("</stderr>");
+
+            let failure = HelmCommandFailure {
+                subcommand: self.args.subcommand(),
+                release_name: self.release_name,
+                namespace: self.namespace,
+                exit_code: output.exit_code,
+                stdout: redactor.redact_text(&String::from_utf8_lossy(&output.stdout)),
+                stderr,
+            };
+
+            Err(HelmWrapperError::from_failure(failure))
+        }
+    }
+}
+
+pub(crate) fn list_command(
+    settings: &HelmSettings,
+    namespace: Option<&NonBlankString>,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("ls");
+
+    if let Some(namespace) = namespace {
+        info!("- namespace '{namespace}'");
+        args.option("-n", namespace.to_string());
+    }
+
+    args.option("-o", "json");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, namespace, None)
+}
+
+pub(crate) fn install_or_upgrade_command(
+    settings: &HelmSettings,
+    request: &InstallOrUpgradeRequest,
+) -> Result<HelmCommand, HelmWrapperError> {
+    let mut args = HelmArgs::new("upgrade");
+
+    let (chart, chart_version) = chart_and_version(request);
+
+    args.arg("--install")
+        .option("-n", request.namespace.to_string())
+        .arg(request.release_name.to_string())
+        .arg(chart);
+
+    if let Some(chart_version) = chart_version {
+        info!("- chart version '{chart_version}'");
+        args.option("--version", chart_version);
+    }
+
+    let temp_files = append_values(settings, request, &mut args)?;
+
+    args.flag("--atomic", request.atomic)
+        .flag("--wait", request.wait)
+        .flag("--wait-for-jobs", request.wait_for_jobs)
+        .flag("--create-namespace", request.create_namespace)
+        .flag("--reuse-values", request.reuse_values)
+        .flag("--reset-values", request.reset_values);
+
+    if let Some(description) = &request.description {
+        info!(
+            "- description '{}'",
+            settings.redactor.redact_text(description)
+        );
+        args.option("--description", description);
+    }
+
+    for (k, v) in &request.labels {
+        info!("- label '{k}': '{}'", settings.redactor.redact_value(k, v));
+        args.option("--labels", format!("{k}={v}"));
+    }
+
+    match request.dry_run {
+        Some(DryRun::Client) => {
+            info!("- dry run (client)");
+            args.arg("--dry-run=client");
+        }
+        Some(DryRun::Server) => {
+            info!("- dry run (server)");
+            args.arg("--dry-run=server");
+        }
+        None => {}
+    }
+
+    for helm_option in &request.helm_options {
+        info!(
+            "- helm option '{}'",
+            settings.redactor.redact_text(helm_option)
+        );
+        args.arg(helm_option.to_string());
+    }
+
+    settings.append_global_args(&mut args);
+
+    args.option("-o", "json")
+        .option("--timeout", settings.timeout_arg());
+
+    let mut command = HelmCommand::new(args, Some(&request.namespace), Some(&request.release_name));
+    command.temp_files = temp_files;
+
+    Ok(command)
+}
+
+/// Chart reference and version for helm. Tag of OCI reference (`oci://host/chart:1.0.0`)
+/// is passed as `--version`, unless chart version is set explicitly.
+fn chart_and_version(request: &InstallOrUpgradeRequest) -> (String, Option<String>) {
+    let chart = ChartReference::parse(&request.chart_name);
+
+    match (&request.chart_version, chart.tag()) {
+        (Some(chart_version), _) => (
+            request.chart_name.to_string(),
+            Some(chart_version.to_string()),
+        ),
+        (None, Some(tag)) => (chart.without_tag().to_string(), Some(tag.to_string())),
+        (None, None) => (request.chart_name.to_string(), None),
+    }
+}
+
+/// Append values sources and overrides in the given order.
+/// Returns temp files with in-memory values, which must live until helm exits.
+fn append_values(
+    settings: &HelmSettings,
+    request: &InstallOrUpgradeRequest,
+    args: &mut HelmArgs,
+) -> Result<Vec<NamedTempFile>, HelmWrapperError> {
+    let temp_files = append_values_sources(settings, &request.values, args)?;
+
+    if !request.value_overrides.is_empty() && !settings.unsafe_mode {
+        info!("overriden chart values won't be mentioned in log because of safe mode");
+    }
+
+    for value_override in &request.value_overrides {
+        if settings.unsafe_mode {
+            info!(
+                "- value override '{}': '{}'",
+                value_override.key,
+                settings
+                    .redactor
+                    .redact_value(&value_override.key, &value_override.value)
+            );
+        }
+        args.option(
+            value_override.kind.flag(),
+            format!("{}={}", value_override.key, value_override.value),
+        );
+    }
+
+    Ok(temp_files)
+}
+
+/// Append values files and in-memory values (`-f`) in the given order.
+/// Returns temp files with in-memory values, which must live until helm exits.
+fn append_values_sources(
+    settings: &HelmSettings,
+    values: &[ValuesSource],
+    args: &mut HelmArgs,
+) -> Result<Vec<NamedTempFile>, HelmWrapperError> {
+    let mut temp_files = vec![];
+
+    for source in values {
+        match source {
+            ValuesSource::File(path) => {
+                info!(
+                    "- values file '{}'",
+                    settings.redactor.redact_text(&path.display().to_string())
+                );
+                args.option("-f", path);
+            }
+            ValuesSource::Inline(values) => {
+                let values_file = write_values_file(settings, values)?;
+                args.option("-f", values_file.path());
+                temp_files.push(values_file);
+            }
+        }
+    }
+
+    Ok(temp_files)
+}
+
+/// Write in-memory values to temp file readable by owner only (0600), which is removed on drop.
+/// JSON is valid YAML, so helm reads it as regular values file.
+fn write_values_file(
+    settings: &HelmSettings,
+    values: &serde_json::Value,
+) -> Result<NamedTempFile, HelmWrapperError> {
+    let content = serde_json::to_string(values)?;
+
+    let mut values_file = tempfile::Builder::new()
+        .prefix("helm-values-")
+        .suffix(".json")
+        .tempfile()?;
+
+    values_file.write_all(content.as_bytes())?;
+    values_file.flush()?;
+
+    if settings.unsafe_mode {
+        info!(
+            "- inline values '{}': {}",
+            values_file.path().display(),
+            settings.redactor.redact_json(values)
+        );
+    } else {
+        info!(
+            "- inline values '{}' won't be mentioned in log because of safe mode",
+            values_file.path().display()
+        );
+    }
+
+    Ok(values_file)
+}
+
+pub(crate) fn template_command(
+    settings: &HelmSettings,
+    request: &InstallOrUpgradeRequest,
+) -> Result<HelmCommand, HelmWrapperError> {
+    let mut args = HelmArgs::new("template");
+
+    let (chart, chart_version) = chart_and_version(request);
+
+    args.arg(request.release_name.to_string())
+        .arg(chart)
+        .option("-n", request.namespace.to_string());
+
+    if let Some(chart_version) = chart_version {
+        info!("- chart version '{chart_version}'");
+        args.option("--version", chart_version);
+    }
+
+    let temp_files = append_values(settings, request, &mut args)?;
+
+    for helm_option in &request.helm_options {
+        info!(
+            "- helm option '{}'",
+            settings.redactor.redact_text(helm_option)
+        );
+        args.arg(helm_option.to_string());
+    }
+
+    settings.append_global_args(&mut args);
+
+    let mut command = HelmCommand::new(args, Some(&request.namespace), Some(&request.release_name));
+    command.temp_files = temp_files;
+
+    Ok(command)
+}
+
+pub(crate) fn lint_command(
+    settings: &HelmSettings,
+    chart_path: &Path,
+    values: &[ValuesSource],
+    strict: bool,
+) -> Result<HelmCommand, HelmWrapperError> {
+    let mut args = HelmArgs::new("lint");
+
+    args.arg(chart_path);
+
+    let temp_files = append_values_sources(settings, values, &mut args)?;
+
+    if strict {
+        info!("- strict");
+        args.arg("--strict");
+    }
+
+    settings.append_global_args(&mut args);
+
+    let mut command = HelmCommand::new(args, None, None);
+    command.temp_files = temp_files;
+
+    Ok(command)
+}
+
+/// `helm lint` exits with error if any chart failed, report is still printed to stdout
+pub(crate) fn read_lint_report(
+    result: Result<String, HelmWrapperError>,
+) -> Result<LintReport, HelmWrapperError> {
+    match result {
+        Ok(stdout) => Ok(parse_lint_report(&stdout)),
+        Err(e) => match e.failure() {
+            Some(failure) if failure.stdout.contains("==> Linting") => {
+                let mut report = parse_lint_report(&failure.stdout);
+
+                if let Some((linted, failed)) = failure.stderr.lines().find_map(parse_summary) {
+                    report.linted = linted;
+                    report.failed = failed;
+                }
+
+                Ok(report)
+            }
+            _ => Err(e),
+        },
+    }
+}
+
+pub(crate) fn repo_add_command(
+    settings: &HelmSettings,
+    name: &NonBlankString,
+    url: &NonBlankString,
+    options: &RepoAddOptions,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("repo");
+
+    args.arg("add").arg(name.to_string()).arg(url.to_string());
+
+    let mut stdin = None;
+
+    if let Some(credentials) = &options.credentials {
+        info!(
+            "- username '{}'",
+            settings
+                .redactor
+                .redact_value("username", &credentials.username)
+        );
+        args.option("--username", &credentials.username)
+            .arg("--password-stdin");
+        stdin = Some(credentials.password.clone());
+    }
+
+    if let Some(ca_file) = &options.ca_file {
+        info!("- ca file '{}'", ca_file.display());
+        args.option("--ca-file", ca_file);
+    }
+
+    args.flag("--pass-credentials", options.pass_credentials)
+        .flag(
+            "--insecure-skip-tls-verify",
+            options.insecure_skip_tls_verify,
+        )
+        .flag("--force-update", options.force_update);
+
+    settings.append_global_args(&mut args);
+
+    let mut command = HelmCommand::new(args, None, None);
+    command.stdin = stdin;
+
+    command
+}
+
+pub(crate) fn repo_update_command(
+    settings: &HelmSettings,
+    names: &[NonBlankString],
+) -> HelmCommand {
+    let mut args = HelmArgs::new("repo");
+
+    args.arg("update");
+
+    for name in names {
+        info!("- repository '{name}'");
+        args.arg(name.to_string());
+    }
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+pub(crate) fn repo_list_command(settings: &HelmSettings) -> HelmCommand {
+    let mut args = HelmArgs::new("repo");
+
+    args.arg("list").option("-o", "json");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+pub(crate) fn repo_remove_command(settings: &HelmSettings, name: &NonBlankString) -> HelmCommand {
+    let mut args = HelmArgs::new("repo");
+
+    args.arg("remove").arg(name.to_string());
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+/// `helm repo list` exits with error if there are no repositories
+pub(crate) fn read_repo_list(
+    result: Result<String, HelmWrapperError>,
+) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+    match result {
+        Ok(stdout) => Ok(serde_json::from_str(&stdout)?),
+        Err(e) => match e.failure() {
+            Some(failure) if failure.stderr.contains("no repositories to show") => Ok(vec![]),
+            _ => Err(e),
+        },
+    }
+}
+
+pub(crate) fn search_repo_command(
+    settings: &HelmSettings,
+    keyword: &NonBlankString,
+    versions: bool,
+    devel: bool,
+    version_constraint: Option<&NonBlankString>,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("search");
+
+    args.arg("repo").arg(keyword.to_string());
+
+    if let Some(version_constraint) = version_constraint {
+        info!("- version constraint '{version_constraint}'");
+        args.option("--version", version_constraint.to_string());
+    }
+
+    args.flag("--versions", versions)
+        .flag("--devel", devel)
+        .option("-o", "json");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+pub(crate) fn registry_login_command(
+    settings: &HelmSettings,
+    host: &NonBlankString,
+    credentials: &RepoCredentials,
+    options: &RegistryLoginOptions,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("registry");
+
+    info!(
+        "- username '{}'",
+        settings
+            .redactor
+            .redact_value("username", &credentials.username)
+    );
+
+    args.arg("login")
+        .arg(host.to_string())
+        .option("--username", &credentials.username)
+        .arg("--password-stdin")
+        .flag("--insecure", options.insecure);
+
+    for (name, path) in [
+        ("--ca-file", &options.ca_file),
+        ("--cert-file", &options.cert_file),
+        ("--key-file", &options.key_file),
+    ] {
+        if let Some(path) = path {
+            info!("- {} '{}'", name.trim_start_matches('-'), path.display());
+            args.option(name, path);
+        }
+    }
+
+    settings.append_global_args(&mut args);
+
+    let mut command = HelmCommand::new(args, None, None);
+    command.stdin = Some(credentials.password.clone());
+
+    command
+}
+
+pub(crate) fn registry_logout_command(
+    settings: &HelmSettings,
+    host: &NonBlankString,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("registry");
+
+    args.arg("logout").arg(host.to_string());
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+pub(crate) fn push_command(
+    settings: &HelmSettings,
+    chart_package: &Path,
+    remote: &NonBlankString,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("push");
+
+    args.arg(chart_package).arg(remote.to_string());
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+pub(crate) fn pull_command(
+    settings: &HelmSettings,
+    chart: &NonBlankString,
+    chart_version: Option<&NonBlankString>,
+    destination: &Path,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("pull");
+
+    args.arg(chart.to_string());
+
+    if let Some(chart_version) = chart_version {
+        info!("- chart version '{chart_version}'");
+        args.option("--version", chart_version.to_string());
+    }
+
+    info!("- destination '{}'", destination.display());
+    args.option("--destination", destination);
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, None, None)
+}
+
+pub(crate) fn uninstall_command(
+    settings: &HelmSettings,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("uninstall");
+
+    args.arg(release_name.to_string())
+        .option("-n", namespace.to_string())
+        .option("--timeout", settings.timeout_arg())
+        .arg("--wait");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, Some(namespace), Some(release_name))
+}
+
+pub(crate) fn rollback_command(
+    settings: &HelmSettings,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+    revision: u32,
+    options: &RollbackOptions,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("rollback");
+
+    args.arg(release_name.to_string())
+        .arg(revision.to_string())
+        .option("-n", namespace.to_string())
+        .option("--timeout", settings.timeout_arg())
+        .flag("--wait", options.wait)
+        .flag("--cleanup-on-fail", options.cleanup_on_fail)
+        .flag("--force", options.force)
+        .flag("--recreate-pods", options.recreate_pods);
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, Some(namespace), Some(release_name))
+}
+
+pub(crate) fn history_command(
+    settings: &HelmSettings,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+    max: Option<u32>,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("history");
+
+    args.arg(release_name.to_string())
+        .option("-n", namespace.to_string());
+
+    if let Some(max) = max {
+        info!("- max revisions {max}");
+        args.option("--max", max.to_string());
+    }
+
+    args.option("-o", "json");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, Some(namespace), Some(release_name))
+}
+
+pub(crate) fn status_command(
+    settings: &HelmSettings,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+    revision: Option<u32>,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("status");
+
+    args.arg(release_name.to_string())
+        .option("-n", namespace.to_string());
+
+    if let Some(revision) = revision {
+        info!("- revision {revision}");
+        args.option("--revision", revision.to_string());
+    }
+
+    args.arg("--show-resources").option("-o", "json");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, Some(namespace), Some(release_name))
+}
+
+/// What `helm get` should return
+pub(crate) enum GetSubject {
+    /// User supplied values, or computed values if `all` is set
+    Values {
+        all: bool,
+    },
+    Manifest,
+    Notes,
+    Hooks,
+    Metadata,
+}
+
+pub(crate) fn get_command(
+    settings: &HelmSettings,
+    subject: GetSubject,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+    revision: Option<u32>,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("get");
+
+    match subject {
+        GetSubject::Values { all } => args.arg("values").flag("--all", all),
+        GetSubject::Manifest => args.arg("manifest"),
+        GetSubject::Notes => args.arg("notes"),
+        GetSubject::Hooks => args.arg("hooks"),
+        GetSubject::Metadata => args.arg("metadata"),
+    };
+
+    args.arg(release_name.to_string())
+        .option("-n", namespace.to_string());
+
+    if let Some(revision) = revision {
+        info!("- revision {revision}");
+        args.option("--revision", revision.to_string());
+    }
+
+    if matches!(subject, GetSubject::Values { .. } | GetSubject::Metadata) {
+        args.option("-o", "json");
+    }
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, Some(namespace), Some(release_name))
+}
+
+/// Strip `NOTES:` header which `helm get notes` prints before notes
+pub(crate) fn parse_notes(stdout: &str) -> String {
+    stdout
+        .strip_prefix("NOTES:")
+        .unwrap_or(stdout)
+        .trim_start_matches(['\r', '\n'])
+        .to_string()
+}
+
+/// List single release in any status, used to get its current revision
+pub(crate) fn release_list_command(
+    settings: &HelmSettings,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+) -> HelmCommand {
+    let mut args = HelmArgs::new("ls");
+
+    args.option("-n", namespace.to_string())
+        .arg("--all")
+        .option(
+            "--filter",
+            format!("^{}$", release_name.replace('.', "\\.")),
+        )
+        .option("-o", "json");
+
+    settings.append_global_args(&mut args);
+
+    HelmCommand::new(args, Some(namespace), Some(release_name))
+}
+
+/// Revision preceding the current one, helm rolls back to it when revision isn't specified
+pub(crate) fn previous_revision(
+    release_name: &NonBlankString,
+    releases: &[HelmListItem],
+) -> Result<u32, HelmWrapperError> {
+    releases
+        .iter()
+        .find(|release| release.name == release_name.as_ref())
+        .and_then(|release| release.revision.parse::<u32>().ok())
+        .and_then(|revision| revision.checked_sub(1))
+        .filter(|revision| *revision > 0)
+        .ok_or_else(|| HelmWrapperError::RollbackRevisionNotFound(release_name.to_string()))
+}
+
+#[cfg(test)]
+mod command_tests {
+    use std::path::Path;
+
+    use non_blank_string_rs::NonBlankString;
+
+    use crate::{
+        args::HelmArgs,
+        command::{
+            get_command, history_command, install_or_upgrade_command, lint_command, list_command,
+            parse_notes, previous_revision, pull_command, push_command, read_lint_report,
+            read_repo_list, registry_login_command, registry_logout_command, repo_add_command,
+            repo_list_command, repo_remove_command, repo_update_command, rollback_command,
+            search_repo_command, status_command, template_command, uninstall_command, GetSubject,
+            HelmSettings,
+        },
+        error::{HelmCommandFailure, HelmWrapperError},
+        redact::Redactor,
+        registry::RegistryLoginOptions,
+        repo::{RepoAddOptions, RepoCredentials},
+        request::{DryRun, InstallOrUpgradeRequest, ValuesSource},
+        runner::CommandOutput,
+        HelmDeployStatus, HelmListItem, RollbackOptions,
+    };
+
+    fn to_strings(args: HelmArgs) -> Vec<String> {
+        args.iter()
+            .map(|arg| arg.to_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn install_or_upgrade_args_keep_special_chars_intact() {
+        let settings = HelmSettings {
+            kubeconfig_path: Some("/home/user/kube configs/dev config".to_string()),
+            ..HelmSettings::default()
+        };
+
+        let values_overrides: Vec<(NonBlankString, String)> = vec![(
+            "ingress.annotations.description".parse().unwrap(),
+            "it's \"quoted\", a=b and spaced".to_string(),
+        )];
+
+        let values_file = Path::new("/tmp/my values/values prod.yaml");
+
+        let request = InstallOrUpgradeRequest::from_args(
+            &"whoami".parse().unwrap(),
+            &"whoami".parse().unwrap(),
+            &"cowboysysop/whoami".parse().unwrap(),
+            Some(&"5.2.0".parse().unwrap()),
+            Some(values_overrides.as_slice()),
+            Some(values_file),
+            Some(&vec!["--create-namespace".parse().unwrap()]),
+        );
+
+        let command = install_or_upgrade_command(&settings, &request).unwrap();
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "upgrade",
+                "--install",
+                "-n",
+                "whoami",
+                "whoami",
+                "cowboysysop/whoami",
+                "--version",
+                "5.2.0",
+                "-f",
+                "/tmp/my values/values prod.yaml",
+                "--set",
+                "ingress.annotations.description=it's \"quoted\"\\, a=b and spaced",
+                "--create-namespace",
+                "--kubeconfig",
+                "/home/user/kube configs/dev config",
+                "-o",
+                "json",
+                "--timeout",
+                "15s",
+            ]
+        );
+    }
+
+    #[test]
+    fn install_or_upgrade_args_from_request_builder() {
+        let settings = HelmSettings::default();
+
+        let request = InstallOrUpgradeRequest::new(
+            "whoami".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .values_file("values.yaml")
+        .values_file("values-prod.yaml")
+        .set("replicaCount", "2")
+        .set_string("image.tag", "1.10")
+        .set_file("config", "config.toml")
+        .set_json("resources", r#"{"limits":{"cpu":"100m"}}"#)
+        .atomic(true)
+        .wait(true)
+        .wait_for_jobs(true)
+        .create_namespace(true)
+        .reuse_values(true)
+        .description("deploy #42")
+        .label("team", "core")
+        .label("env", "prod")
+        .dry_run(DryRun::Server)
+        .helm_option("--skip-crds".parse().unwrap());
+
+        let command = install_or_upgrade_command(&settings, &request).unwrap();
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "upgrade",
+                "--install",
+                "-n",
+                "whoami",
+                "whoami",
+                "cowboysysop/whoami",
+                "-f",
+                "values.yaml",
+                "-f",
+                "values-prod.yaml",
+                "--set",
+                "replicaCount=2",
+                "--set-string",
+                "image.tag=1.10",
+                "--set-file",
+                "config=config.toml",
+                "--set-json",
+                r#"resources={"limits":{"cpu":"100m"}}"#,
+                "--atomic",
+                "--wait",
+                "--wait-for-jobs",
+                "--create-namespace",
+                "--reuse-values",
+                "--description",
+                "deploy #42",
+                "--labels",
+                "env=prod",
+                "--labels",
+                "team=core",
+                "--dry-run=server",
+                "--skip-crds",
+                "-o",
+                "json",
+                "--timeout",
+                "15s",
+            ]
+        );
+    }
+
+    #[test]
+    fn list_args_always_request_json_output() {
+        let settings = HelmSettings {
+            debug: true,
+            ..HelmSettings::default()
+        };
+
+        assert_eq!(
+            to_strings(list_command(&settings, None).args),
+            vec!["ls", "-o", "json", "--debug"]
+        );
+
+        assert_eq!(
+            to_strings(list_command(&settings, Some(&"whoami".parse().unwrap())).args),
+            vec!["ls", "-n", "whoami", "-o", "json", "--debug"]
+        );
+    }
+
+    #[test]
+    fn uninstall_args_with_kubeconfig_path_with_spaces() {
+        let settings = HelmSettings {
+            kubeconfig_path: Some("/home/user/my config".to_string()),
+            timeout: 30,
+            ..HelmSettings::default()
+        };
+
+        let command = uninstall_command(
+            &settings,
+            &"whoami".parse().unwrap(),
+            &"whoami".parse().unwrap(),
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "uninstall",
+                "whoami",
+                "-n",
+                "whoami",
+                "--timeout",
+                "30s",
+                "--wait",
+                "--kubeconfig",
+                "/home/user/my config",
+            ]
+        );
+    }
+
+    #[test]
+    fn rollback_args_with_all_options() {
+        let settings = HelmSettings::default();
+
+        let options = RollbackOptions {
+            wait: true,
+            cleanup_on_fail: true,
+            force: true,
+            recreate_pods: true,
+        };
+
+        let command = rollback_command(
+            &settings,
+            &"whoami".parse().unwrap(),
+            &"whoami".parse().unwrap(),
+            3,
+            &options,
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "rollback",
+                "whoami",
+                "3",
+                "-n",
+                "whoami",
+                "--timeout",
+                "15s",
+                "--wait",
+                "--cleanup-on-fail",
+                "--force",
+                "--recreate-pods",
+            ]
+        );
+    }
+
+    #[test]
+    fn previous_revision_of_release() {
+        let release = HelmListItem {
+            name: "whoami".to_string(),
+            namespace: "whoami".to_string(),
+            revision: "4".to_string(),
+            updated: "2025-06-01 10:00:00.000000000 +0000 UTC".to_string(),
+            status: HelmDeployStatus::Failed,
+            chart: "whoami-5.2.0".to_string(),
+            app_version: "1.10.3".to_string(),
+        };
+
+        let release_name: NonBlankString = "whoami".parse().unwrap();
+
+        assert_eq!(
+            previous_revision(&release_name, std::slice::from_ref(&release)).unwrap(),
+            3
+        );
+
+        let first_release = HelmListItem {
+            revision: "1".to_string(),
+            ..release
+        };
+
+        assert!(matches!(
+            previous_revision(&release_name, &[first_release]),
+            Err(HelmWrapperError::RollbackRevisionNotFound(_))
+        ));
+
+        assert!(previous_revision(&release_name, &[]).is_err());
+    }
+
+    #[test]
+    fn history_args_with_max_revisions() {
+        let settings = HelmSettings::default();
+
+        let command = history_command(
+            &settings,
+            &"whoami".parse().unwrap(),
+            &"whoami".parse().unwrap(),
+            Some(5),
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec!["history", "whoami", "-n", "whoami", "--max", "5", "-o", "json"]
+        );
+    }
+
+    #[test]
+    fn status_args_with_revision() {
+        let settings = HelmSettings::default();
+
+        let command = status_command(
+            &settings,
+            &"whoami".parse().unwrap(),
+            &"whoami".parse().unwrap(),
+            Some(2),
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "status",
+                "whoami",
+                "-n",
+                "whoami",
+                "--revision",
+                "2",
+                "--show-resources",
+                "-o",
+                "json"
+            ]
+        );
+    }
+
+    #[test]
+    fn get_args_for_every_subject() {
+        let settings = HelmSettings::default();
+        let namespace: NonBlankString = "whoami".parse().unwrap();
+        let release_name: NonBlankString = "whoami".parse().unwrap();
+
+        let command = get_command(
+            &settings,
+            GetSubject::Values { all: true },
+            &namespace,
+            &release_name,
+            Some(3),
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "get",
+                "values",
+                "--all",
+                "whoami",
+                "-n",
+                "whoami",
+                "--revision",
+                "3",
+                "-o",
+                "json"
+            ]
+        );
+
+        let command = get_command(
+            &settings,
+            GetSubject::Manifest,
+            &namespace,
+            &release_name,
+            None,
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec!["get", "manifest", "whoami", "-n", "whoami"]
+        );
+
+        let command = get_command(
+            &settings,
+            GetSubject::Metadata,
+            &namespace,
+            &release_name,
+            None,
+        );
+
+        assert_eq!(
+            to_strings(command.args),
+            vec!["get", "metadata", "whoami", "-n", "whoami", "-o", "json"]
+        );
+    }
+
+    #[test]
+    fn strip_notes_header() {
+        assert_eq!(
+            parse_notes("NOTES:\n1. Get the application URL\n"),
+            "1. Get the application URL\n"
+        );
+        assert_eq!(parse_notes(""), "");
+    }
+
+    #[test]
+    fn template_args_take_values_and_skip_install_options() {
+        let settings = HelmSettings::default();
+
+        let request = InstallOrUpgradeRequest::new(
+            "demo".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .chart_version("5.2.0".parse().unwrap())
+        .values_file("values.yaml")
+        .set("replicaCount", "2")
+        .atomic(true)
+        .label("team", "a")
+        .helm_option("--skip-tests".parse().unwrap());
+
+        let command = template_command(&settings, &request).unwrap();
+
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "template",
+                "whoami",
+                "cowboysysop/whoami",
+                "-n",
+                "demo",
+                "--version",
+                "5.2.0",
+                "-f",
+                "values.yaml",
+                "--set",
+                "replicaCount=2",
+                "--skip-tests",
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_args() {
+        let settings = HelmSettings::default();
+
+        let command = lint_command(
+            &settings,
+            Path::new("charts/whoami"),
+            &[ValuesSource::File("values.yaml".into())],
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            to_strings(command.args),
+            vec!["lint", "charts/whoami", "-f", "values.yaml", "--strict"]
+        );
+    }
+
+    #[test]
+    fn failed_lint_is_read_as_report() {
+        let failure = HelmCommandFailure {
+            subcommand: "lint".to_string(),
+            release_name: None,
+            namespace: None,
+            exit_code: Some(1),
+            stdout: "==> Linting charts/whoami\n[ERROR] Chart.yaml: version is required\n"
+                .to_string(),
+            stderr: "Error: 1 chart(s) linted, 1 chart(s) failed\n".to_string(),
+        };
+
+        let report = read_lint_report(Err(HelmWrapperError::from_failure(failure))).unwrap();
+
+        assert_eq!(report.linted, 1);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.errors().count(), 1);
+    }
+
+    #[test]
+    fn lint_execution_error_is_kept() {
+        let failure = HelmCommandFailure {
+            subcommand: "lint".to_string(),
+            release_name: None,
+            namespace: None,
+            exit_code: Some(1),
+            stdout: String::new(),
+            stderr: "Error: unknown flag: --strct\n".to_string(),
+        };
+
+        assert!(read_lint_report(Err(HelmWrapperError::from_failure(failure))).is_err());
+    }
+
+    #[test]
+    fn repo_add_args_pass_password_through_stdin() {
+        let settings = HelmSettings::default();
+
+        let options = RepoAddOptions {
+            credentials: Some(RepoCredentials::new("admin", "p4ss")),
+            pass_credentials: true,
+            ca_file: Some("/etc/ssl/ca.pem".into()),
+            insecure_skip_tls_verify: false,
+            force_update: true,
+        };
+
+        let command = repo_add_command(
+            &settings,
+            &"private".parse().unwrap(),
+            &"https://charts.example.com".parse().unwrap(),
+            &options,
+        );
+
+        assert_eq!(command.stdin.as_deref(), Some("p4ss"));
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "repo",
+                "add",
+                "private",
+                "https://charts.example.com",
+                "--username",
+                "admin",
+                "--password-stdin",
+                "--ca-file",
+                "/etc/ssl/ca.pem",
+                "--pass-credentials",
+                "--force-update",
+            ]
+        );
+    }
+
+    #[test]
+    fn repo_password_is_masked_in_failure() {
+        let settings = HelmSettings::default();
+
+        let options = RepoAddOptions {
+            credentials: Some(RepoCredentials::new("admin", "p4ss")),
+            ..RepoAddOptions::default()
+        };
+
+        let command = repo_add_command(
+            &settings,
+            &"private".parse().unwrap(),
+            &"https://charts.example.com".parse().unwrap(),
+            &options,
+        );
+
+        let invocation = command.invocation(&settings);
+
+        assert_eq!(invocation.program, "helm");
+        assert_eq!(invocation.stdin.as_deref(), Some("p4ss"));
+
+        let output = CommandOutput::failure(1, "Error: 401 for admin:p4ss");
+
+        let error = command.read_output(&settings, output).unwrap_err();
+
+        assert_eq!(error.failure().unwrap().stderr, "Error: 401 for admin:***");
+    }
+
+    #[test]
+    fn repo_update_and_list_args() {
+        let settings = HelmSettings::default();
+
+        assert_eq!(
+            to_strings(repo_update_command(&settings, &[]).args),
+            vec!["repo", "update"]
+        );
+        assert_eq!(
+            to_strings(
+                repo_update_command(&settings, &["a".parse().unwrap(), "b".parse().unwrap()]).args
+            ),
+            vec!["repo", "update", "a", "b"]
+        );
+        assert_eq!(
+            to_strings(repo_list_command(&settings).args),
+            vec!["repo", "list", "-o", "json"]
+        );
+        assert_eq!(
+            to_strings(repo_remove_command(&settings, &"a".parse().unwrap()).args),
+            vec!["repo", "remove", "a"]
+        );
+    }
+
+    #[test]
+    fn repo_list_without_repositories_is_empty() {
+        let failure = HelmCommandFailure {
+            subcommand: "repo".to_string(),
+            release_name: None,
+            namespace: None,
+            exit_code: Some(1),
+            stdout: String::new(),
+            stderr: "Error: no repositories to show\n".to_string(),
+        };
+
+        assert!(read_repo_list(Err(HelmWrapperError::from_failure(failure)))
+            .unwrap()
+            .is_empty());
+
+        let repositories = read_repo_list(Ok(
+            r#"[{"name":"cowboysysop","url":"https://cowboysysop.github.io/charts/"}]"#.to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(repositories[0].name, "cowboysysop");
+    }
+
+    #[test]
+    fn search_repo_args() {
+        let settings = HelmSettings::default();
+
+        assert_eq!(
+            to_strings(
+                search_repo_command(
+                    &settings,
+                    &"whoami".parse().unwrap(),
+                    true,
+                    true,
+                    Some(&"^5.0.0".parse().unwrap()),
+                )
+                .args
+            ),
+            vec![
+                "search",
+                "repo",
+                "whoami",
+                "--version",
+                "^5.0.0",
+                "--versions",
+                "--devel",
+                "-o",
+                "json"
+            ]
+        );
+    }
+
+    #[test]
+    fn oci_tag_is_passed_as_chart_version() {
+        let settings = HelmSettings::default();
+
+        let request = InstallOrUpgradeRequest::new(
+            "whoami".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "oci://ghcr.io/charts/whoami:5.2.0".parse().unwrap(),
+        );
+
+        let args = to_strings(
+            install_or_upgrade_command(&settings, &request)
+                .unwrap()
+                .args,
+        );
+
+        assert_eq!(
+            &args[5..8],
+            &["oci://ghcr.io/charts/whoami", "--version", "5.2.0"]
+        );
+
+        let request = request.chart_version("5.3.0".parse().unwrap());
+
+        let args = to_strings(template_command(&settings, &request).unwrap().args);
+
+        assert_eq!(
+            &args[2..7],
+            &[
+                "oci://ghcr.io/charts/whoami:5.2.0",
+                "-n",
+                "whoami",
+                "--version",
+                "5.3.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_login_args_pass_password_through_stdin() {
+        let settings = HelmSettings::default();
+
+        let options = RegistryLoginOptions {
+            insecure: true,
+            ca_file: Some("/etc/ssl/ca.pem".into()),
+            ..RegistryLoginOptions::default()
+        };
+
+        let command = registry_login_command(
+            &settings,
+            &"ghcr.io".parse().unwrap(),
+            &RepoCredentials::new("bot", "t0ken"),
+            &options,
+        );
+
+        assert_eq!(command.stdin.as_deref(), Some("t0ken"));
+        assert_eq!(
+            to_strings(command.args),
+            vec![
+                "registry",
+                "login",
+                "ghcr.io",
+                "--username",
+                "bot",
+                "--password-stdin",
+                "--insecure",
+                "--ca-file",
+                "/etc/ssl/ca.pem",
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_logout_push_pull_args() {
+        let settings = HelmSettings::default();
+
+        assert_eq!(
+            to_strings(registry_logout_command(&settings, &"ghcr.io".parse().unwrap()).args),
+            vec!["registry", "logout", "ghcr.io"]
+        );
+        assert_eq!(
+            to_strings(
+                push_command(
+                    &settings,
+                    Path::new("dist/whoami-5.2.0.tgz"),
+                    &"oci://ghcr.io/charts".parse().unwrap()
+                )
+                .args
+            ),
+            vec!["push", "dist/whoami-5.2.0.tgz", "oci://ghcr.io/charts"]
+        );
+        assert_eq!(
+            to_strings(
+                pull_command(
+                    &settings,
+                    &"oci://ghcr.io/charts/whoami".parse().unwrap(),
+                    Some(&"5.2.0".parse().unwrap()),
+                    Path::new("charts"),
+                )
+                .args
+            ),
+            vec![
+                "pull",
+                "oci://ghcr.io/charts/whoami",
+                "--version",
+                "5.2.0",
+                "--destination",
+                "charts"
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_values_are_passed_through_owner_only_temp_file() {
+        let settings = HelmSettings::default();
+
+        let request = InstallOrUpgradeRequest::new(
+            "whoami".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .values_file("values.yaml")
+        .values_json(serde_json::json!({"db": {"password": "s3cr3t"}}));
+
+        let command = install_or_upgrade_command(&settings, &request).unwrap();
+
+        let args = to_strings(command.args.clone());
+        let values_file_path = args[9].clone();
+
+        assert_eq!(&args[6..9], &["-f", "values.yaml", "-f"]);
+        assert!(values_file_path.ends_with(".json"));
+
+        assert_eq!(
+            std::fs::read_to_string(&values_file_path).unwrap(),
+            r#"{"db":{"password":"s3cr3t"}}"#
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(&values_file_path)
+                .unwrap()
+                .permissions()
+                .mode();
+
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        drop(command);
+
+        assert!(!Path::new(&values_file_path).exists());
+    }
+
+    #[test]
+    fn failure_payload_is_redacted() {
+        let settings = HelmSettings {
+            redactor: Redactor::new().sensitive_key("password").secret("s3cr3t"),
+            ..HelmSettings::default()
+        };
+
+        let request = InstallOrUpgradeRequest::new(
+            "whoami".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .set("db.password", "qwerty");
+
+        let command = install_or_upgrade_command(&settings, &request).unwrap();
+
+        let output = CommandOutput {
+            exit_code: Some(1),
+            stdout: b"db:\n  password: qwerty\n".to_vec(),
+            stderr: b"Error: failed to parse db.password=qwerty, token s3cr3t".to_vec(),
+        };
+
+        let error = command.read_output(&settings, output).unwrap_err();
+        let failure = error.failure().unwrap();
+
+        assert_eq!(failure.exit_code, Some(1));
+        assert_eq!(failure.stdout, "db:\n  password: ***\n");
+        assert_eq!(
+            failure.stderr,
+            "Error: failed to parse db.password=***, token ***"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fmt, path::PathBuf};
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,6 +13,215 @@ pub enum HelmWrapperError {
     #[error("Helm command execution error")]
     ExecutionError(#[from] std::io::Error),
 
+    #[error("Helm release not found: {0}")]
+    ReleaseNotFound(Box<HelmCommandFailure>),
+
+    #[error("Another helm operation is in progress: {0}")]
+    OperationInProgress(Box<HelmCommandFailure>),
+
+    #[error("Helm command timed out: {0}")]
+    Timeout(Box<HelmCommandFailure>),
+
+    #[error("Helm chart not found: {0}")]
+    ChartNotFound(Box<HelmCommandFailure>),
+
+    #[error("Kubernetes cluster unreachable: {0}")]
+    ClusterUnreachable(Box<HelmCommandFailure>),
+
+    #[error("Unable to parse YAML: {0}")]
+    YamlParseError(Box<HelmCommandFailure>),
+
+    #[error("Helm command execution error: {0}")]
+    CommandFailed(Box<HelmCommandFailure>),
+
+    #[deprecated(
+        note = "failed helm commands are reported with typed variants, e.g. `CommandFailed`"
+    )]
     #[error("Helm command execution error")]
     Error,
+
+    #[error("Values file '{}' is not readable", path.display())]
+    ValuesFileError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Release label '{0}' is invalid, key must be non-empty and key and value can't contain ',' or '='")]
+    InvalidLabel(String),
+
+    #[error("Unable to serialize chart values")]
+    ValuesSerializationError(#[source] serde_json::Error),
+
+    #[error("Unable to find previous revision of helm release '{0}'")]
+    RollbackRevisionNotFound(String),
+}
+
+impl HelmWrapperError {
+    /// Map failed helm command to error variant by well-known helm messages in stderr.
+    /// Unknown failures are returned as `CommandFailed`.
+    pub fn from_failure(failure: HelmCommandFailure) -> Self {
+        let failure = Box::new(failure);
+        let stderr = failure.stderr.to_lowercase();
+
+        let has = |patterns: &[&str]| patterns.iter().any(|pattern| stderr.contains(pattern));
+
+        if has(&["another operation (install/upgrade/rollback) is in progress"]) {
+            HelmWrapperError::OperationInProgress(failure)
+        } else if has(&["kubernetes cluster unreachable"]) {
+            HelmWrapperError::ClusterUnreachable(failure)
+        } else if has(&[
+            "timed out waiting for the condition",
+            "context deadline exceeded",
+        ]) {
+            HelmWrapperError::Timeout(failure)
+        } else if has(&["release: not found", "has no deployed releases"]) {
+            HelmWrapperError::ReleaseNotFound(failure)
+        } else if has(&[
+            "failed to download",
+            "chart not found",
+            "no chart version found",
+            "no chart name found",
+            "not found in repository",
+        ]) || (stderr.contains("error: repo ") && stderr.contains(" not found"))
+        {
+            HelmWrapperError::ChartNotFound(failure)
+        } else if has(&[
+            "yaml:",
+            "error converting yaml to json",
+            "cannot unmarshal",
+            "failed to parse",
+        ]) {
+            HelmWrapperError::YamlParseError(failure)
+        } else {
+            HelmWrapperError::CommandFailed(failure)
+        }
+    }
+
+    /// Details of failed helm command, if error was caused by one
+    pub fn failure(&self) -> Option<&HelmCommandFailure> {
+        match self {
+            HelmWrapperError::ReleaseNotFound(failure)
+            | HelmWrapperError::OperationInProgress(failure)
+            | HelmWrapperError::Timeout(failure)
+            | HelmWrapperError::ChartNotFound(failure)
+            | HelmWrapperError::ClusterUnreachable(failure)
+            | HelmWrapperError::YamlParseError(failure)
+            | HelmWrapperError::CommandFailed(failure) => Some(failure),
+            _ => None,
+        }
+    }
+}
+
+/// Helm command which finished with non-zero exit code
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelmCommandFailure {
+    /// Helm subcommand, e.g. `upgrade`
+    pub subcommand: String,
+    pub release_name: Option<String>,
+    pub namespace: Option<String>,
+    /// Exit code, `None` if helm was terminated by signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl fmt::Display for HelmCommandFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "helm {}", self.subcommand)?;
+
+        if let Some(release_name) = &self.release_name {
+            write!(f, ", release '{release_name}'")?;
+        }
+
+        if let Some(namespace) = &self.namespace {
+            write!(f, ", namespace '{namespace}'")?;
+        }
+
+        match self.exit_code {
+            Some(exit_code) => write!(f, ", exit code {exit_code}")?,
+            None => write!(f, ", terminated by signal")?,
+        }
+
+        let stderr = self.stderr.trim();
+
+        if !stderr.is_empty() {
+            write!(f, ": {stderr}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use crate::error::{HelmCommandFailure, HelmWrapperError};
+
+    fn failure(stderr: &str) -> HelmCommandFailure {
+        HelmCommandFailure {
+            subcommand: "upgrade".to_string(),
+            release_name: Some("whoami".to_string()),
+            namespace: Some("demo".to_string()),
+            exit_code: Some(1),
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    #[test]
+    fn classify_well_known_helm_failures() {
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: uninstall: Release not loaded: whoami: release: not found",
+        ));
+        assert!(matches!(error, HelmWrapperError::ReleaseNotFound(_)));
+
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: UPGRADE FAILED: \"whoami\" has no deployed releases",
+        ));
+        assert!(matches!(error, HelmWrapperError::ReleaseNotFound(_)));
+
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: UPGRADE FAILED: another operation (install/upgrade/rollback) is in progress",
+        ));
+        assert!(matches!(error, HelmWrapperError::OperationInProgress(_)));
+
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: UPGRADE FAILED: timed out waiting for the condition",
+        ));
+        assert!(matches!(error, HelmWrapperError::Timeout(_)));
+
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: failed to download \"cowboysysop/whoami\" at version \"99.0.0\"",
+        ));
+        assert!(matches!(error, HelmWrapperError::ChartNotFound(_)));
+
+        let error = HelmWrapperError::from_failure(failure("Error: repo cowboysysop not found"));
+        assert!(matches!(error, HelmWrapperError::ChartNotFound(_)));
+
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: Kubernetes cluster unreachable: Get \"https://127.0.0.1:6443/version\": dial tcp 127.0.0.1:6443: connect: connection refused",
+        ));
+        assert!(matches!(error, HelmWrapperError::ClusterUnreachable(_)));
+
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: failed to parse values.yml: error converting YAML to JSON: yaml: line 2: could not find expected ':'",
+        ));
+        assert!(matches!(error, HelmWrapperError::YamlParseError(_)));
+
+        let error = HelmWrapperError::from_failure(failure("Error: something unexpected"));
+        assert!(matches!(error, HelmWrapperError::CommandFailed(_)));
+    }
+
+    #[test]
+    fn error_message_contains_command_context_and_stderr() {
+        let error = HelmWrapperError::from_failure(failure(
+            "Error: UPGRADE FAILED: timed out waiting for the condition\n",
+        ));
+
+        assert_eq!(
+            error.to_string(),
+            "Helm command timed out: helm upgrade, release 'whoami', namespace 'demo', exit code 1: Error: UPGRADE FAILED: timed out waiting for the condition"
+        );
+
+        assert_eq!(error.failure().unwrap().exit_code, Some(1));
+    }
 }
@@ -1,3 +1,5 @@
+use std::{collections::HashMap, fmt};
+
 use serde::Deserialize;
 
 #[cfg(feature = "nonblocking")]
@@ -8,6 +10,39 @@ pub mod blocking;
 
 pub mod error;
 
+pub mod lint;
+
+pub mod manifest;
+
+#[cfg(any(feature = "blocking-mock", feature = "nonblocking-mock"))]
+pub mod mock;
+
+pub mod overrides;
+
+pub mod redact;
+
+pub mod registry;
+
+pub mod repo;
+
+pub mod request;
+
+pub mod runner;
+
+pub mod search;
+
+#[cfg(any(feature = "blocking-mock", feature = "nonblocking-mock"))]
+pub mod simulator;
+
+#[cfg(any(feature = "blocking", feature = "nonblocking"))]
+mod args;
+
+#[cfg(any(feature = "blocking", feature = "nonblocking"))]
+mod command;
+
+#[cfg(any(feature = "blocking", feature = "nonblocking"))]
+pub mod replay;
+
 #[cfg(feature = "blocking-mock")]
 pub mod blocking_mock;
 
@@ -28,6 +63,218 @@ pub struct HelmListItem {
     pub app_version: String,
 }
 
+/// Release revision from `helm history`
+#[derive(Deserialize, Debug, Clone)]
+pub struct HelmRevision {
+    pub revision: u32,
+    pub updated: String,
+    pub status: HelmDeployStatus,
+    pub chart: String,
+    pub app_version: String,
+    pub description: String,
+}
+
+/// Release object returned by `helm status` and `helm upgrade --install`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct HelmRelease {
+    pub name: String,
+    pub namespace: String,
+    /// Release revision
+    pub version: u32,
+    pub info: HelmReleaseInfo,
+    #[serde(default)]
+    pub chart: Option<HelmChart>,
+    /// Values supplied by user for the release
+    #[serde(default)]
+    pub config: serde_json::Value,
+    /// Rendered manifest of the release
+    #[serde(default)]
+    pub manifest: String,
+    #[serde(default)]
+    pub hooks: Vec<HelmHook>,
+}
+
+impl HelmRelease {
+    /// Chart default values merged with values supplied by user, as helm computes them.
+    /// `null` user value removes the key.
+    pub fn computed_values(&self) -> serde_json::Value {
+        let mut values = self
+            .chart
+            .as_ref()
+            .map(|chart| chart.values.clone())
+            .unwrap_or(serde_json::Value::Null);
+
+        merge_values(&mut values, &self.config);
+
+        values
+    }
+}
+
+pub(crate) fn merge_values(target: &mut serde_json::Value, source: &serde_json::Value) {
+    match (target, source) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(source)) => {
+            for (key, value) in source {
+                if value.is_null() {
+                    target.remove(key);
+                } else {
+                    merge_values(
+                        target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                        value,
+                    );
+                }
+            }
+        }
+        (target, source) => {
+            if !source.is_null() {
+                *target = source.clone();
+            }
+        }
+    }
+}
+
+/// Release details from `helm status -o json --show-resources`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(from = "HelmRelease")]
+pub struct HelmReleaseStatus {
+    pub name: String,
+    pub namespace: String,
+    pub revision: u32,
+    /// Status, deploy times, description, notes and resources
+    pub info: HelmReleaseInfo,
+    /// Metadata of deployed chart
+    pub chart: Option<HelmChartMetadata>,
+    /// Values supplied by user for the release
+    pub config: serde_json::Value,
+    /// Rendered manifest of the release
+    pub manifest: String,
+}
+
+impl HelmReleaseStatus {
+    pub fn status(&self) -> &HelmDeployStatus {
+        &self.info.status
+    }
+
+    /// Rendered NOTES.txt
+    pub fn notes(&self) -> Option<&str> {
+        self.info.notes.as_deref()
+    }
+
+    /// Release resources grouped by kind, e.g. `v1/Service`
+    pub fn resources(&self) -> &HashMap<String, Vec<serde_json::Value>> {
+        &self.info.resources
+    }
+}
+
+impl From<HelmRelease> for HelmReleaseStatus {
+    fn from(release: HelmRelease) -> Self {
+        Self {
+            name: release.name,
+            namespace: release.namespace,
+            revision: release.version,
+            info: release.info,
+            chart: release.chart.map(|chart| chart.metadata),
+            config: release.config,
+            manifest: release.manifest,
+        }
+    }
+}
+
+/// Release metadata from `helm get metadata`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HelmReleaseMetadata {
+    pub name: String,
+    /// Chart name
+    pub chart: String,
+    /// Chart version
+    pub version: String,
+    #[serde(default)]
+    pub app_version: String,
+    pub namespace: String,
+    pub revision: u32,
+    pub status: HelmDeployStatus,
+    #[serde(default)]
+    pub deployed_at: String,
+    #[serde(default)]
+    pub annotations: HashMap<String, String>,
+}
+
+/// Release hook, e.g. `pre-install` job
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct HelmHook {
+    pub name: String,
+    pub kind: String,
+    /// Template path within the chart
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub manifest: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub weight: i32,
+    #[serde(default)]
+    pub delete_policies: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct HelmReleaseInfo {
+    #[serde(default)]
+    pub first_deployed: String,
+    #[serde(default)]
+    pub last_deployed: String,
+    #[serde(default)]
+    pub deleted: String,
+    #[serde(default)]
+    pub description: String,
+    pub status: HelmDeployStatus,
+    /// Rendered NOTES.txt
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Release resources grouped by kind, e.g. `v1/Service`
+    #[serde(default)]
+    pub resources: HashMap<String, Vec<serde_json::Value>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct HelmChart {
+    pub metadata: HelmChartMetadata,
+    /// Chart default values
+    #[serde(default)]
+    pub values: serde_json::Value,
+}
+
+/// Chart.yaml content
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HelmChartMetadata {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub app_version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub api_version: Option<String>,
+    #[serde(default, rename = "type")]
+    pub chart_type: Option<String>,
+    #[serde(default)]
+    pub kube_version: Option<String>,
+    #[serde(default)]
+    pub home: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub annotations: HashMap<String, String>,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+/// Kept for compatibility, `install_or_upgrade_release` returns `HelmRelease`
 #[derive(Deserialize, Debug, Clone)]
 pub struct HelmUpgradeResponse {
     pub info: HelmUpgradeResponseInfo,
@@ -38,14 +285,267 @@ pub struct HelmUpgradeResponseInfo {
     pub status: HelmDeployStatus,
 }
 
-#[derive(PartialEq, Deserialize, Debug, Clone)]
+/// Options for `helm rollback`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RollbackOptions {
+    /// Wait until all resources are in ready state (`--wait`)
+    pub wait: bool,
+    /// Delete new resources created in this rollback when it fails (`--cleanup-on-fail`)
+    pub cleanup_on_fail: bool,
+    /// Force resource updates through delete/recreate if needed (`--force`)
+    pub force: bool,
+    /// Restart pods for the resources if applicable (`--recreate-pods`)
+    pub recreate_pods: bool,
+}
+
+/// Helm release status
+#[derive(PartialEq, Eq, Hash, Deserialize, Debug, Clone, Default)]
+#[serde(from = "String")]
 pub enum HelmDeployStatus {
-    #[serde(rename = "deployed")]
+    #[default]
+    Unknown,
     Deployed,
-    #[serde(rename = "pending-install")]
+    Uninstalled,
+    Superseded,
+    Failed,
+    Uninstalling,
     PendingInstall,
-    #[serde(rename = "pending-upgrade")]
     PendingUpgrade,
-    #[serde(rename = "failed")]
-    Failed,
+    PendingRollback,
+    /// Status not known to this library version, e.g. introduced by newer helm
+    Other(String),
+}
+
+impl HelmDeployStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HelmDeployStatus::Unknown => "unknown",
+            HelmDeployStatus::Deployed => "deployed",
+            HelmDeployStatus::Uninstalled => "uninstalled",
+            HelmDeployStatus::Superseded => "superseded",
+            HelmDeployStatus::Failed => "failed",
+            HelmDeployStatus::Uninstalling => "uninstalling",
+            HelmDeployStatus::PendingInstall => "pending-install",
+            HelmDeployStatus::PendingUpgrade => "pending-upgrade",
+            HelmDeployStatus::PendingRollback => "pending-rollback",
+            HelmDeployStatus::Other(status) => status,
+        }
+    }
+
+    /// Helm operation on release is still in progress
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            HelmDeployStatus::PendingInstall
+                | HelmDeployStatus::PendingUpgrade
+                | HelmDeployStatus::PendingRollback
+                | HelmDeployStatus::Uninstalling
+        )
+    }
+
+    /// Release won't change its status without another helm operation
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            HelmDeployStatus::Deployed
+                | HelmDeployStatus::Uninstalled
+                | HelmDeployStatus::Superseded
+                | HelmDeployStatus::Failed
+        )
+    }
+
+    /// Release is deployed and running
+    pub fn is_healthy(&self) -> bool {
+        *self == HelmDeployStatus::Deployed
+    }
+}
+
+impl From<String> for HelmDeployStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "unknown" => HelmDeployStatus::Unknown,
+            "deployed" => HelmDeployStatus::Deployed,
+            "uninstalled" => HelmDeployStatus::Uninstalled,
+            "superseded" => HelmDeployStatus::Superseded,
+            "failed" => HelmDeployStatus::Failed,
+            "uninstalling" => HelmDeployStatus::Uninstalling,
+            "pending-install" => HelmDeployStatus::PendingInstall,
+            "pending-upgrade" => HelmDeployStatus::PendingUpgrade,
+            "pending-rollback" => HelmDeployStatus::PendingRollback,
+            _ => HelmDeployStatus::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for HelmDeployStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod helm_deploy_status_tests {
+    use crate::HelmDeployStatus;
+
+    fn parse(status: &str) -> HelmDeployStatus {
+        serde_json::from_str(&format!("\"{status}\"")).unwrap()
+    }
+
+    #[test]
+    fn deserialize_every_helm_release_status() {
+        assert_eq!(parse("unknown"), HelmDeployStatus::Unknown);
+        assert_eq!(parse("deployed"), HelmDeployStatus::Deployed);
+        assert_eq!(parse("uninstalled"), HelmDeployStatus::Uninstalled);
+        assert_eq!(parse("superseded"), HelmDeployStatus::Superseded);
+        assert_eq!(parse("failed"), HelmDeployStatus::Failed);
+        assert_eq!(parse("uninstalling"), HelmDeployStatus::Uninstalling);
+        assert_eq!(parse("pending-install"), HelmDeployStatus::PendingInstall);
+        assert_eq!(parse("pending-upgrade"), HelmDeployStatus::PendingUpgrade);
+        assert_eq!(parse("pending-rollback"), HelmDeployStatus::PendingRollback);
+    }
+
+    #[test]
+    fn deserialize_unknown_status_as_other() {
+        let status = parse("pending-something");
+
+        assert_eq!(
+            status,
+            HelmDeployStatus::Other("pending-something".to_string())
+        );
+        assert_eq!(status.to_string(), "pending-something");
+        assert!(!status.is_pending());
+        assert!(!status.is_terminal());
+        assert!(!status.is_healthy());
+    }
+
+    #[test]
+    fn status_helpers() {
+        assert!(HelmDeployStatus::PendingRollback.is_pending());
+        assert!(HelmDeployStatus::Uninstalling.is_pending());
+        assert!(!HelmDeployStatus::Deployed.is_pending());
+
+        assert!(HelmDeployStatus::Superseded.is_terminal());
+        assert!(HelmDeployStatus::Failed.is_terminal());
+        assert!(!HelmDeployStatus::PendingInstall.is_terminal());
+
+        assert!(HelmDeployStatus::Deployed.is_healthy());
+        assert!(!HelmDeployStatus::Failed.is_healthy());
+    }
+}
+
+#[cfg(test)]
+mod helm_revision_tests {
+    use crate::{HelmDeployStatus, HelmRevision};
+
+    #[test]
+    fn deserialize_helm_history_output() {
+        let output = r#"[
+            {"revision":1,"updated":"2025-06-01T10:00:00.123456789Z","status":"superseded","chart":"whoami-5.1.0","app_version":"1.10.2","description":"Install complete"},
+            {"revision":2,"updated":"2025-06-02T10:00:00.123456789Z","status":"deployed","chart":"whoami-5.2.0","app_version":"1.10.3","description":"Upgrade complete"}
+        ]"#;
+
+        let revisions: Vec<HelmRevision> = serde_json::from_str(output).unwrap();
+
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].revision, 1);
+        assert_eq!(revisions[0].status, HelmDeployStatus::Superseded);
+        assert_eq!(revisions[1].chart, "whoami-5.2.0");
+        assert_eq!(revisions[1].app_version, "1.10.3");
+        assert_eq!(revisions[1].description, "Upgrade complete");
+    }
+}
+
+#[cfg(test)]
+mod helm_release_tests {
+    use crate::{HelmDeployStatus, HelmRelease, HelmReleaseStatus};
+
+    #[test]
+    fn deserialize_helm_status_output() {
+        let output = r#"{
+            "name": "whoami",
+            "info": {
+                "first_deployed": "2025-06-01T10:00:00.123456789Z",
+                "last_deployed": "2025-06-02T10:00:00.123456789Z",
+                "deleted": "",
+                "description": "Upgrade complete",
+                "status": "deployed",
+                "notes": "1. Get the application URL",
+                "resources": {
+                    "v1/Service": [{"apiVersion": "v1", "kind": "Service", "metadata": {"name": "whoami"}}]
+                }
+            },
+            "chart": {
+                "metadata": {
+                    "name": "whoami",
+                    "version": "5.2.0",
+                    "description": "Tiny Go webserver",
+                    "apiVersion": "v2",
+                    "appVersion": "1.10.3",
+                    "type": "application"
+                },
+                "templates": []
+            },
+            "config": {"replicaCount": 2},
+            "manifest": "---\napiVersion: v1\nkind: Service\n",
+            "version": 2,
+            "namespace": "whoami"
+        }"#;
+
+        let status: HelmReleaseStatus = serde_json::from_str(output).unwrap();
+
+        assert_eq!(status.name, "whoami");
+        assert_eq!(status.namespace, "whoami");
+        assert_eq!(status.revision, 2);
+        assert_eq!(status.status(), &HelmDeployStatus::Deployed);
+        assert_eq!(status.info.last_deployed, "2025-06-02T10:00:00.123456789Z");
+        assert_eq!(status.notes(), Some("1. Get the application URL"));
+        assert_eq!(status.resources()["v1/Service"].len(), 1);
+
+        let chart = status.chart.unwrap();
+        assert_eq!(chart.version, "5.2.0");
+        assert_eq!(chart.app_version.as_deref(), Some("1.10.3"));
+        assert_eq!(chart.chart_type.as_deref(), Some("application"));
+
+        assert_eq!(status.config["replicaCount"], 2);
+        assert!(status.manifest.contains("kind: Service"));
+    }
+
+    #[test]
+    fn compute_values_from_chart_defaults_and_user_config() {
+        let output = r#"{
+            "name": "whoami",
+            "info": {"status": "deployed"},
+            "chart": {
+                "metadata": {"name": "whoami", "version": "5.2.0"},
+                "values": {
+                    "replicaCount": 1,
+                    "ingress": {"enabled": false, "className": "nginx"},
+                    "startupProbe": {"enabled": true}
+                }
+            },
+            "config": {
+                "replicaCount": 2,
+                "ingress": {"enabled": true},
+                "startupProbe": null
+            },
+            "hooks": [
+                {"name": "whoami-test", "kind": "Pod", "path": "whoami/templates/tests/test.yaml", "events": ["test"]}
+            ],
+            "version": 3,
+            "namespace": "whoami"
+        }"#;
+
+        let release: HelmRelease = serde_json::from_str(output).unwrap();
+
+        assert_eq!(release.hooks.len(), 1);
+        assert_eq!(release.hooks[0].events, vec!["test"]);
+
+        assert_eq!(
+            release.computed_values(),
+            serde_json::json!({
+                "replicaCount": 2,
+                "ingress": {"enabled": true, "className": "nginx"}
+            })
+        );
+    }
 }
@@ -0,0 +1,271 @@
+use std::fmt;
+
+/// Severity of `helm lint` finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintSeverity {
+    fn parse(severity: &str) -> Option<Self> {
+        match severity {
+            "INFO" => Some(LintSeverity::Info),
+            "WARNING" => Some(LintSeverity::Warning),
+            "ERROR" => Some(LintSeverity::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintSeverity::Info => "INFO",
+            LintSeverity::Warning => "WARNING",
+            LintSeverity::Error => "ERROR",
+        }
+    }
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Single `helm lint` finding, e.g. `[WARNING] templates/service.yaml: message`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    pub severity: LintSeverity,
+    /// File path relative to chart, e.g. `templates/service.yaml`, if helm reports it
+    pub file: Option<String>,
+    pub message: String,
+}
+
+/// Findings of single linted chart
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChartLintResult {
+    /// Chart path as passed to helm
+    pub chart: String,
+    pub findings: Vec<LintFinding>,
+}
+
+impl ChartLintResult {
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == LintSeverity::Error)
+    }
+}
+
+/// Parsed `helm lint` output
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LintReport {
+    pub charts: Vec<ChartLintResult>,
+    /// Number of linted charts from helm summary
+    pub linted: usize,
+    /// Number of failed charts from helm summary, with `--strict` warnings fail chart too
+    pub failed: usize,
+}
+
+impl LintReport {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && !self.charts.iter().any(ChartLintResult::has_errors)
+    }
+
+    /// Findings of all charts with given severity
+    pub fn findings(&self, severity: LintSeverity) -> impl Iterator<Item = &LintFinding> {
+        self.charts
+            .iter()
+            .flat_map(|chart| chart.findings.iter())
+            .filter(move |finding| finding.severity == severity)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &LintFinding> {
+        self.findings(LintSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &LintFinding> {
+        self.findings(LintSeverity::Warning)
+    }
+}
+
+/// Parse `helm lint` output:
+///
+/// ```text
+/// ==> Linting ./whoami
+/// [INFO] Chart.yaml: icon is recommended
+/// [ERROR] templates/: parse error at (whoami/templates/service.yaml:3): unexpected EOF
+///
+/// Error: 1 chart(s) linted, 1 chart(s) failed
+/// ```
+///
+/// Lines which don't start new finding are appended to the message of previous one.
+/// If summary is missing, counters are calculated from findings.
+pub fn parse_lint_report(output: &str) -> LintReport {
+    let mut report = LintReport::default();
+    let mut summary: Option<(usize, usize)> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(chart) = trimmed.strip_prefix("==> Linting") {
+            report.charts.push(ChartLintResult {
+                chart: chart.trim().to_string(),
+                findings: vec![],
+            });
+            continue;
+        }
+
+        if let Some(counters) = parse_summary(trimmed) {
+            summary = Some(counters);
+            continue;
+        }
+
+        let Some(chart) = report.charts.last_mut() else {
+            continue;
+        };
+
+        if let Some(finding) = parse_finding(trimmed) {
+            chart.findings.push(finding);
+        } else if !trimmed.is_empty() {
+            if let Some(finding) = chart.findings.last_mut() {
+                finding.message.push('\n');
+                finding.message.push_str(trimmed);
+            }
+        }
+    }
+
+    (report.linted, report.failed) = summary.unwrap_or_else(|| {
+        let failed = report
+            .charts
+            .iter()
+            .filter(|chart| chart.has_errors())
+            .count();
+
+        (report.charts.len(), failed)
+    });
+
+    report
+}
+
+/// `[SEVERITY] file: message` or `Error message` for chart which can't be loaded
+fn parse_finding(line: &str) -> Option<LintFinding> {
+    if let Some(message) = line.strip_prefix("Error ") {
+        return Some(LintFinding {
+            severity: LintSeverity::Error,
+            file: None,
+            message: message.to_string(),
+        });
+    }
+
+    let (severity, rest) = line.strip_prefix('[')?.split_once(']')?;
+    let severity = LintSeverity::parse(severity)?;
+    let rest = rest.trim_start();
+
+    let (file, message) = match rest.split_once(": ") {
+        Some((file, message)) if !file.contains(' ') => {
+            let file = (!file.is_empty()).then(|| file.to_string());
+            (file, message.to_string())
+        }
+        _ => match rest.strip_prefix(": ") {
+            Some(message) => (None, message.to_string()),
+            None => (None, rest.to_string()),
+        },
+    };
+
+    Some(LintFinding {
+        severity,
+        file,
+        message,
+    })
+}
+
+/// `N chart(s) linted, M chart(s) failed`, optionally prefixed with `Error: `
+pub(crate) fn parse_summary(line: &str) -> Option<(usize, usize)> {
+    let line = line.strip_prefix("Error: ").unwrap_or(line);
+    let (linted, failed) = line.split_once(" chart(s) linted, ")?;
+    let failed = failed.strip_suffix(" chart(s) failed")?;
+
+    Some((linted.trim().parse().ok()?, failed.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod lint_tests {
+    use crate::lint::{parse_lint_report, LintFinding, LintSeverity};
+
+    #[test]
+    fn parse_failed_lint_output() {
+        let output = r#"==> Linting ./whoami
+[INFO] Chart.yaml: icon is recommended
+[WARNING] templates/service.yaml: object name does not conform to Kubernetes naming requirements: "Whoami"
+[ERROR] templates/: parse error at (whoami/templates/deployment.yaml:12): unexpected EOF
+line two of error
+
+==> Linting ./broken
+Error unable to check Chart.yaml file in chart: stat broken/Chart.yaml: no such file or directory
+
+Error: 2 chart(s) linted, 2 chart(s) failed
+"#;
+
+        let report = parse_lint_report(output);
+
+        assert_eq!(report.linted, 2);
+        assert_eq!(report.failed, 2);
+        assert!(!report.is_success());
+
+        assert_eq!(report.charts.len(), 2);
+        assert_eq!(report.charts[0].chart, "./whoami");
+        assert_eq!(
+            report.charts[0].findings,
+            vec![
+                LintFinding {
+                    severity: LintSeverity::Info,
+                    file: Some("Chart.yaml".to_string()),
+                    message: "icon is recommended".to_string(),
+                },
+                LintFinding {
+                    severity: LintSeverity::Warning,
+                    file: Some("templates/service.yaml".to_string()),
+                    message: "object name does not conform to Kubernetes naming requirements: \"Whoami\"".to_string(),
+                },
+                LintFinding {
+                    severity: LintSeverity::Error,
+                    file: Some("templates/".to_string()),
+                    message: "parse error at (whoami/templates/deployment.yaml:12): unexpected EOF\nline two of error".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(report.charts[1].chart, "./broken");
+        assert_eq!(report.charts[1].findings[0].severity, LintSeverity::Error);
+        assert_eq!(report.charts[1].findings[0].file, None);
+
+        assert_eq!(report.errors().count(), 2);
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn parse_successful_lint_output() {
+        let output = "==> Linting test-data/whoami\n[INFO] Chart.yaml: icon is recommended\n\n1 chart(s) linted, 0 chart(s) failed\n";
+
+        let report = parse_lint_report(output);
+
+        assert!(report.is_success());
+        assert_eq!(report.linted, 1);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.findings(LintSeverity::Info).count(), 1);
+    }
+
+    #[test]
+    fn counters_are_calculated_without_summary() {
+        let output = "==> Linting a\n[ERROR] : unable to load chart\n==> Linting b\n";
+
+        let report = parse_lint_report(output);
+
+        assert_eq!(report.linted, 2);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.charts[0].findings[0].file, None);
+        assert_eq!(report.charts[0].findings[0].message, "unable to load chart");
+    }
+}
@@ -0,0 +1,202 @@
+/// Single Kubernetes document from rendered helm manifest
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ManifestDocument {
+    pub api_version: Option<String>,
+    pub kind: Option<String>,
+    /// `metadata.name`
+    pub name: Option<String>,
+    /// `metadata.namespace`
+    pub namespace: Option<String>,
+    /// Chart template path from `# Source:` comment, e.g. `whoami/templates/service.yaml`
+    pub source: Option<String>,
+    /// Document YAML as rendered by helm
+    pub raw: String,
+}
+
+/// Split multi-document YAML rendered by helm (`helm get manifest`, `helm template`)
+/// into documents. Only identifying fields are extracted, `raw` keeps the whole document
+/// for further parsing. Documents without any content are skipped.
+pub fn parse_manifest(manifest: &str) -> Vec<ManifestDocument> {
+    let mut documents = vec![];
+    let mut lines: Vec<&str> = vec![];
+
+    for line in manifest.lines() {
+        if is_document_separator(line) {
+            if let Some(document) = parse_document(&lines) {
+                documents.push(document);
+            }
+            lines.clear();
+        } else {
+            lines.push(line);
+        }
+    }
+
+    if let Some(document) = parse_document(&lines) {
+        documents.push(document);
+    }
+
+    documents
+}
+
+fn is_document_separator(line: &str) -> bool {
+    line == "---" || line.starts_with("--- ") || line.starts_with("---\t")
+}
+
+fn parse_document(lines: &[&str]) -> Option<ManifestDocument> {
+    let has_content = lines.iter().any(|line| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+
+    if !has_content {
+        return None;
+    }
+
+    let mut document = ManifestDocument {
+        raw: lines.join("\n").trim_matches('\n').to_string(),
+        ..ManifestDocument::default()
+    };
+
+    // indentation of `metadata` children, known after first child line
+    let mut in_metadata = false;
+    let mut metadata_indent: Option<usize> = None;
+
+    for line in lines {
+        if let Some(source) = line.strip_prefix("# Source:") {
+            if document.source.is_none() {
+                document.source = Some(source.trim().to_string());
+            }
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+
+        if indent == 0 {
+            in_metadata = false;
+
+            match split_key_value(trimmed) {
+                Some(("apiVersion", value)) => document.api_version = value,
+                Some(("kind", value)) => document.kind = value,
+                Some(("metadata", _)) => {
+                    in_metadata = true;
+                    metadata_indent = None;
+                }
+                _ => {}
+            }
+        } else if in_metadata {
+            let child_indent = *metadata_indent.get_or_insert(indent);
+
+            if indent == child_indent {
+                match split_key_value(trimmed) {
+                    Some(("name", value)) => document.name = value,
+                    Some(("namespace", value)) => document.namespace = value,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Some(document)
+}
+
+/// Split `key: value` line, value is unquoted. Returns `None` value for nested blocks.
+fn split_key_value(line: &str) -> Option<(&str, Option<String>)> {
+    let (key, value) = line.split_once(':')?;
+
+    let value = value.trim();
+    let value = value
+        .split_once(" #")
+        .map(|(value, _comment)| value.trim_end())
+        .unwrap_or(value);
+
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value);
+
+    let value = if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    };
+
+    Some((key.trim(), value))
+}
+
+#[cfg(test)]
+mod manifest_tests {
+    use crate::manifest::parse_manifest;
+
+    #[test]
+    fn parse_rendered_manifest_into_documents() {
+        let manifest = r#"---
+# Source: whoami/templates/serviceaccount.yaml
+apiVersion: v1
+kind: ServiceAccount
+metadata:
+  name: whoami
+  labels:
+    name: not-a-name
+---
+# Source: whoami/templates/service.yaml
+apiVersion: v1
+kind: Service
+metadata:
+  labels:
+    app.kubernetes.io/name: whoami
+  name: "whoami-svc"
+  namespace: 'demo'
+spec:
+  type: ClusterIP
+---
+# Source: whoami/templates/empty.yaml
+---
+# Source: whoami/templates/deployment.yaml
+apiVersion: apps/v1 # comment
+kind: Deployment
+metadata:
+    name: whoami
+"#;
+
+        let documents = parse_manifest(manifest);
+
+        assert_eq!(documents.len(), 3);
+
+        assert_eq!(documents[0].kind.as_deref(), Some("ServiceAccount"));
+        assert_eq!(documents[0].name.as_deref(), Some("whoami"));
+        assert_eq!(documents[0].namespace, None);
+        assert_eq!(
+            documents[0].source.as_deref(),
+            Some("whoami/templates/serviceaccount.yaml")
+        );
+
+        assert_eq!(documents[1].api_version.as_deref(), Some("v1"));
+        assert_eq!(documents[1].kind.as_deref(), Some("Service"));
+        assert_eq!(documents[1].name.as_deref(), Some("whoami-svc"));
+        assert_eq!(documents[1].namespace.as_deref(), Some("demo"));
+        assert!(documents[1]
+            .raw
+            .starts_with("# Source: whoami/templates/service.yaml"));
+        assert!(documents[1].raw.ends_with("  type: ClusterIP"));
+
+        assert_eq!(documents[2].api_version.as_deref(), Some("apps/v1"));
+        assert_eq!(documents[2].name.as_deref(), Some("whoami"));
+    }
+
+    #[test]
+    fn parse_empty_manifest() {
+        assert!(parse_manifest("").is_empty());
+        assert!(parse_manifest("---\n# Source: a.yaml\n---\n").is_empty());
+    }
+}
@@ -0,0 +1,701 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use crate::{
+    error::{HelmCommandFailure, HelmWrapperError},
+    registry::RegistryLoginOptions,
+    repo::{RepoAddOptions, RepoCredentials},
+    request::{InstallOrUpgradeRequest, ValuesSource},
+    simulator::SimulatorHelmExecutor,
+    HelmDeployStatus, RollbackOptions,
+};
+
+/// `HelmExecutor` method, `install_or_upgrade*` methods are recorded as `InstallOrUpgrade`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HelmMethod {
+    List,
+    InstallOrUpgrade,
+    Uninstall,
+    Rollback,
+    History,
+    Status,
+    GetValues,
+    GetManifest,
+    GetNotes,
+    GetHooks,
+    GetMetadata,
+    Template,
+    Lint,
+    RepoAdd,
+    RepoUpdate,
+    RepoList,
+    RepoRemove,
+    SearchRepo,
+    RegistryLogin,
+    RegistryLogout,
+    Push,
+    Pull,
+}
+
+impl HelmMethod {
+    /// Helm subcommand of method, e.g. `upgrade`
+    pub fn subcommand(&self) -> &'static str {
+        match self {
+            HelmMethod::List => "ls",
+            HelmMethod::InstallOrUpgrade => "upgrade",
+            HelmMethod::Uninstall => "uninstall",
+            HelmMethod::Rollback => "rollback",
+            HelmMethod::History => "history",
+            HelmMethod::Status => "status",
+            HelmMethod::GetValues
+            | HelmMethod::GetManifest
+            | HelmMethod::GetNotes
+            | HelmMethod::GetHooks
+            | HelmMethod::GetMetadata => "get",
+            HelmMethod::Template => "template",
+            HelmMethod::Lint => "lint",
+            HelmMethod::RepoAdd
+            | HelmMethod::RepoUpdate
+            | HelmMethod::RepoList
+            | HelmMethod::RepoRemove => "repo",
+            HelmMethod::SearchRepo => "search",
+            HelmMethod::RegistryLogin | HelmMethod::RegistryLogout => "registry",
+            HelmMethod::Push => "push",
+            HelmMethod::Pull => "pull",
+        }
+    }
+}
+
+/// Credentials of recorded call, password itself is never kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCredentials {
+    pub username: String,
+    pub has_password: bool,
+}
+
+impl From<&RepoCredentials> for RecordedCredentials {
+    fn from(credentials: &RepoCredentials) -> Self {
+        Self {
+            username: credentials.username.clone(),
+            has_password: !credentials.password.is_empty(),
+        }
+    }
+}
+
+/// Recorded `HelmExecutor` call with its arguments, secrets are left out
+#[derive(Debug, Clone, PartialEq)]
+pub enum HelmCall {
+    List {
+        namespace: Option<String>,
+    },
+    InstallOrUpgrade(InstallOrUpgradeRequest),
+    Uninstall {
+        namespace: String,
+        release_name: String,
+    },
+    Rollback {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+        options: RollbackOptions,
+    },
+    History {
+        namespace: String,
+        release_name: String,
+        max: Option<u32>,
+    },
+    Status {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+    },
+    GetValues {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+        all: bool,
+    },
+    GetManifest {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+    },
+    GetNotes {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+    },
+    GetHooks {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+    },
+    GetMetadata {
+        namespace: String,
+        release_name: String,
+        revision: Option<u32>,
+    },
+    Template(InstallOrUpgradeRequest),
+    Lint {
+        chart_path: PathBuf,
+        values: Vec<ValuesSource>,
+        strict: bool,
+    },
+    RepoAdd {
+        name: String,
+        url: String,
+        credentials: Option<RecordedCredentials>,
+        pass_credentials: bool,
+        ca_file: Option<PathBuf>,
+        insecure_skip_tls_verify: bool,
+        force_update: bool,
+    },
+    RepoUpdate {
+        names: Vec<String>,
+    },
+    RepoList,
+    RepoRemove {
+        name: String,
+    },
+    SearchRepo {
+        keyword: String,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<String>,
+    },
+    RegistryLogin {
+        host: String,
+        credentials: RecordedCredentials,
+        options: RegistryLoginOptions,
+    },
+    RegistryLogout {
+        host: String,
+    },
+    Push {
+        chart_package: PathBuf,
+        remote: String,
+    },
+    Pull {
+        chart: String,
+        chart_version: Option<String>,
+        destination: PathBuf,
+    },
+}
+
+impl HelmCall {
+    pub(crate) fn repo_add(name: &str, url: &str, options: &RepoAddOptions) -> Self {
+        HelmCall::RepoAdd {
+            name: name.to_string(),
+            url: url.to_string(),
+            credentials: options.credentials.as_ref().map(RecordedCredentials::from),
+            pass_credentials: options.pass_credentials,
+            ca_file: options.ca_file.clone(),
+            insecure_skip_tls_verify: options.insecure_skip_tls_verify,
+            force_update: options.force_update,
+        }
+    }
+
+    pub fn method(&self) -> HelmMethod {
+        match self {
+            HelmCall::List { .. } => HelmMethod::List,
+            HelmCall::InstallOrUpgrade(_) => HelmMethod::InstallOrUpgrade,
+            HelmCall::Uninstall { .. } => HelmMethod::Uninstall,
+            HelmCall::Rollback { .. } => HelmMethod::Rollback,
+            HelmCall::History { .. } => HelmMethod::History,
+            HelmCall::Status { .. } => HelmMethod::Status,
+            HelmCall::GetValues { .. } => HelmMethod::GetValues,
+            HelmCall::GetManifest { .. } => HelmMethod::GetManifest,
+            HelmCall::GetNotes { .. } => HelmMethod::GetNotes,
+            HelmCall::GetHooks { .. } => HelmMethod::GetHooks,
+            HelmCall::GetMetadata { .. } => HelmMethod::GetMetadata,
+            HelmCall::Template(_) => HelmMethod::Template,
+            HelmCall::Lint { .. } => HelmMethod::Lint,
+            HelmCall::RepoAdd { .. } => HelmMethod::RepoAdd,
+            HelmCall::RepoUpdate { .. } => HelmMethod::RepoUpdate,
+            HelmCall::RepoList => HelmMethod::RepoList,
+            HelmCall::RepoRemove { .. } => HelmMethod::RepoRemove,
+            HelmCall::SearchRepo { .. } => HelmMethod::SearchRepo,
+            HelmCall::RegistryLogin { .. } => HelmMethod::RegistryLogin,
+            HelmCall::RegistryLogout { .. } => HelmMethod::RegistryLogout,
+            HelmCall::Push { .. } => HelmMethod::Push,
+            HelmCall::Pull { .. } => HelmMethod::Pull,
+        }
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        match self {
+            HelmCall::List { namespace } => namespace.as_deref(),
+            HelmCall::InstallOrUpgrade(request) | HelmCall::Template(request) => {
+                Some(request.namespace.as_ref())
+            }
+            HelmCall::Uninstall { namespace, .. }
+            | HelmCall::Rollback { namespace, .. }
+            | HelmCall::History { namespace, .. }
+            | HelmCall::Status { namespace, .. }
+            | HelmCall::GetValues { namespace, .. }
+            | HelmCall::GetManifest { namespace, .. }
+            | HelmCall::GetNotes { namespace, .. }
+            | HelmCall::GetHooks { namespace, .. }
+            | HelmCall::GetMetadata { namespace, .. } => Some(namespace),
+            _ => None,
+        }
+    }
+
+    pub fn release_name(&self) -> Option<&str> {
+        match self {
+            HelmCall::InstallOrUpgrade(request) | HelmCall::Template(request) => {
+                Some(request.release_name.as_ref())
+            }
+            HelmCall::Uninstall { release_name, .. }
+            | HelmCall::Rollback { release_name, .. }
+            | HelmCall::History { release_name, .. }
+            | HelmCall::Status { release_name, .. }
+            | HelmCall::GetValues { release_name, .. }
+            | HelmCall::GetManifest { release_name, .. }
+            | HelmCall::GetNotes { release_name, .. }
+            | HelmCall::GetHooks { release_name, .. }
+            | HelmCall::GetMetadata { release_name, .. } => Some(release_name),
+            _ => None,
+        }
+    }
+}
+
+type ErrorFactory = Arc<dyn Fn(&HelmCall) -> HelmWrapperError + Send + Sync>;
+
+/// Programmed behaviour of `MockHelmExecutor` for matching calls:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use helm_wrapper_rs::mock::{HelmMethod, MockRule};
+/// use helm_wrapper_rs::HelmDeployStatus;
+///
+/// // the second upgrade of 'whoami' release times out after 50ms
+/// let timeout = MockRule::new(HelmMethod::InstallOrUpgrade)
+///     .release("whoami")
+///     .nth_call(2)
+///     .delay(Duration::from_millis(50))
+///     .failure("Error: UPGRADE FAILED: context deadline exceeded");
+///
+/// // every upgrade of 'broken' release leaves it failed
+/// let failed = MockRule::new(HelmMethod::InstallOrUpgrade)
+///     .release("broken")
+///     .status(HelmDeployStatus::Failed);
+/// ```
+#[derive(Clone)]
+pub struct MockRule {
+    method: Option<HelmMethod>,
+    release_name: Option<String>,
+    nth_call: Option<usize>,
+    delay: Option<Duration>,
+    error: Option<ErrorFactory>,
+    status: Option<HelmDeployStatus>,
+}
+
+impl MockRule {
+    /// Rule for calls of given method
+    pub fn new(method: HelmMethod) -> Self {
+        Self {
+            method: Some(method),
+            ..Self::any()
+        }
+    }
+
+    /// Rule for calls of any method
+    pub fn any() -> Self {
+        Self {
+            method: None,
+            release_name: None,
+            nth_call: None,
+            delay: None,
+            error: None,
+            status: None,
+        }
+    }
+
+    /// Match only calls for given release
+    pub fn release(mut self, release_name: &str) -> Self {
+        self.release_name = Some(release_name.to_string());
+        self
+    }
+
+    /// Apply only to the Nth (starting from 1) matching call, every matching call by default
+    pub fn nth_call(mut self, nth_call: usize) -> Self {
+        self.nth_call = Some(nth_call);
+        self
+    }
+
+    /// Wait before the call is handled
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Return error created by factory for the call
+    pub fn error(
+        mut self,
+        error: impl Fn(&HelmCall) -> HelmWrapperError + Send + Sync + 'static,
+    ) -> Self {
+        self.error = Some(Arc::new(error));
+        self
+    }
+
+    /// Return failed helm command with given stderr, classified as real helm failure,
+    /// e.g. `Error: release: not found` is `ReleaseNotFound`
+    pub fn failure(self, stderr: &str) -> Self {
+        let stderr = stderr.to_string();
+
+        self.error(move |call| {
+            HelmWrapperError::from_failure(HelmCommandFailure {
+                subcommand: call.method().subcommand().to_string(),
+                release_name: call.release_name().map(str::to_string),
+                namespace: call.namespace().map(str::to_string),
+                exit_code: Some(1),
+                stdout: String::new(),
+                stderr: stderr.clone(),
+            })
+        })
+    }
+
+    /// Release status after install/upgrade and rollback, also returned by `status`
+    /// and `get_metadata`
+    pub fn status(mut self, status: HelmDeployStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    fn matches(&self, call: &HelmCall) -> bool {
+        self.method.is_none_or(|method| method == call.method())
+            && self
+                .release_name
+                .as_deref()
+                .is_none_or(|release_name| call.release_name() == Some(release_name))
+    }
+}
+
+impl fmt::Debug for MockRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockRule")
+            .field("method", &self.method)
+            .field("release_name", &self.release_name)
+            .field("nth_call", &self.nth_call)
+            .field("delay", &self.delay)
+            .field("error", &self.error.is_some())
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+/// Mock executor which records calls and returns programmed errors, delays and statuses.
+/// Calls without programmed error are handled by `SimulatorHelmExecutor`.
+///
+/// Clones share recorded calls, rules and simulator state.
+#[derive(Debug, Clone, Default)]
+pub struct MockHelmExecutor {
+    simulator: SimulatorHelmExecutor,
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// Rules with number of matched calls
+    rules: Vec<(MockRule, usize)>,
+    calls: Vec<HelmCall>,
+}
+
+/// Effect of matched rules on a call
+pub(crate) struct MockOutcome {
+    pub delay: Option<Duration>,
+    pub error: Option<HelmWrapperError>,
+    pub status: Option<HelmDeployStatus>,
+}
+
+impl MockHelmExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle calls with given simulator, e.g. with registered charts
+    pub fn with_simulator(mut self, simulator: SimulatorHelmExecutor) -> Self {
+        self.simulator = simulator;
+        self
+    }
+
+    pub fn with_rule(self, rule: MockRule) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    /// Add rule, all matching rules apply: delays are summed, the first error wins
+    pub fn add_rule(&self, rule: MockRule) {
+        self.state().rules.push((rule, 0));
+    }
+
+    /// Recorded calls in order
+    pub fn calls(&self) -> Vec<HelmCall> {
+        self.state().calls.clone()
+    }
+
+    /// Recorded calls of given method
+    pub fn calls_of(&self, method: HelmMethod) -> Vec<HelmCall> {
+        self.state()
+            .calls
+            .iter()
+            .filter(|call| call.method() == method)
+            .cloned()
+            .collect()
+    }
+
+    pub fn simulator(&self) -> &SimulatorHelmExecutor {
+        &self.simulator
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record call and evaluate matching rules
+    pub(crate) fn intercept(&self, call: HelmCall) -> MockOutcome {
+        let mut state = self.state();
+
+        let mut outcome = MockOutcome {
+            delay: None,
+            error: None,
+            status: None,
+        };
+        let mut error: Option<ErrorFactory> = None;
+
+        for (rule, matched) in state.rules.iter_mut() {
+            if !rule.matches(&call) {
+                continue;
+            }
+
+            *matched += 1;
+
+            if rule.nth_call.is_some_and(|nth_call| nth_call != *matched) {
+                continue;
+            }
+
+            if let Some(delay) = rule.delay {
+                outcome.delay = Some(outcome.delay.unwrap_or_default() + delay);
+            }
+
+            if error.is_none() {
+                error = rule.error.clone();
+            }
+
+            if rule.status.is_some() {
+                outcome.status = rule.status.clone();
+            }
+        }
+
+        state.calls.push(call.clone());
+        drop(state);
+
+        // factory is called without lock, it may use the mock itself
+        outcome.error = error.map(|error| error(&call));
+
+        outcome
+    }
+}
+
+#[cfg(all(test, feature = "blocking-mock"))]
+mod mock_tests {
+    use std::time::{Duration, Instant};
+
+    use non_blank_string_rs::NonBlankString;
+
+    use crate::{
+        blocking::HelmExecutor,
+        error::HelmWrapperError,
+        mock::{HelmCall, HelmMethod, MockHelmExecutor, MockRule, RecordedCredentials},
+        registry::RegistryLoginOptions,
+        repo::{RepoAddOptions, RepoCredentials},
+        request::InstallOrUpgradeRequest,
+        HelmDeployStatus,
+    };
+
+    fn name(value: &str) -> NonBlankString {
+        value.parse().unwrap()
+    }
+
+    fn request(release_name: &str) -> InstallOrUpgradeRequest {
+        InstallOrUpgradeRequest::new(name("apps"), name(release_name), name("./whoami-5.2.0.tgz"))
+    }
+
+    #[test]
+    fn calls_are_recorded_with_arguments() {
+        let executor = MockHelmExecutor::new();
+
+        executor
+            .install_or_upgrade_with(&request("whoami"))
+            .unwrap();
+        executor.list(Some(&name("apps"))).unwrap();
+        executor
+            .history(&name("apps"), &name("whoami"), Some(5))
+            .unwrap();
+
+        let calls = executor.calls();
+
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0], HelmCall::InstallOrUpgrade(request("whoami")));
+        assert_eq!(
+            calls[2],
+            HelmCall::History {
+                namespace: "apps".to_string(),
+                release_name: "whoami".to_string(),
+                max: Some(5),
+            }
+        );
+        assert_eq!(executor.calls_of(HelmMethod::List).len(), 1);
+        assert_eq!(executor.calls_of(HelmMethod::Uninstall).len(), 0);
+    }
+
+    #[test]
+    fn passwords_are_not_recorded() {
+        let executor = MockHelmExecutor::new();
+        let credentials = RepoCredentials::new("robot", "s3cret");
+
+        executor
+            .repo_add(
+                &name("private"),
+                &name("https://charts.example.com"),
+                &RepoAddOptions {
+                    credentials: Some(credentials.clone()),
+                    ..RepoAddOptions::default()
+                },
+            )
+            .unwrap();
+        executor
+            .registry_login(
+                &name("registry.example.com"),
+                &credentials,
+                &RegistryLoginOptions::default(),
+            )
+            .unwrap();
+
+        let recorded = RecordedCredentials {
+            username: "robot".to_string(),
+            has_password: true,
+        };
+
+        let calls = executor.calls();
+
+        assert!(matches!(
+            &calls[0],
+            HelmCall::RepoAdd { credentials: Some(credentials), .. } if *credentials == recorded
+        ));
+        assert!(matches!(
+            &calls[1],
+            HelmCall::RegistryLogin { credentials, .. } if *credentials == recorded
+        ));
+        assert!(!format!("{calls:?}").contains("s3cret"));
+    }
+
+    #[test]
+    fn error_on_nth_call_of_release() {
+        let executor = MockHelmExecutor::new().with_rule(
+            MockRule::new(HelmMethod::InstallOrUpgrade)
+                .release("whoami")
+                .nth_call(2)
+                .failure("Error: UPGRADE FAILED: another operation (install/upgrade/rollback) is in progress"),
+        );
+
+        executor.install_or_upgrade_with(&request("other")).unwrap();
+        executor
+            .install_or_upgrade_with(&request("whoami"))
+            .unwrap();
+
+        let error = executor
+            .install_or_upgrade_with(&request("whoami"))
+            .unwrap_err();
+
+        assert!(matches!(error, HelmWrapperError::OperationInProgress(_)));
+        assert_eq!(
+            error.failure().unwrap().release_name.as_deref(),
+            Some("whoami")
+        );
+        assert_eq!(error.failure().unwrap().subcommand, "upgrade");
+
+        let release = executor
+            .install_or_upgrade_with(&request("whoami"))
+            .unwrap();
+
+        assert_eq!(release.version, 2, "failed call doesn't change state");
+        assert_eq!(executor.calls().len(), 4);
+    }
+
+    #[test]
+    fn custom_error_and_status() {
+        let executor = MockHelmExecutor::new();
+
+        executor.add_rule(
+            MockRule::new(HelmMethod::Uninstall)
+                .error(|call| HelmWrapperError::RollbackRevisionNotFound(format!("{call:?}"))),
+        );
+        executor
+            .add_rule(MockRule::new(HelmMethod::InstallOrUpgrade).status(HelmDeployStatus::Failed));
+        executor
+            .add_rule(MockRule::new(HelmMethod::Status).status(HelmDeployStatus::PendingUpgrade));
+
+        let release = executor
+            .install_or_upgrade_with(&request("whoami"))
+            .unwrap();
+
+        assert_eq!(release.info.status, HelmDeployStatus::Failed);
+        assert_eq!(
+            executor.list(Some(&name("apps"))).unwrap()[0].status,
+            HelmDeployStatus::Failed
+        );
+        assert_eq!(
+            executor
+                .status(&name("apps"), &name("whoami"), None)
+                .unwrap()
+                .info
+                .status,
+            HelmDeployStatus::PendingUpgrade
+        );
+        assert!(matches!(
+            executor.uninstall(&name("apps"), &name("whoami")),
+            Err(HelmWrapperError::RollbackRevisionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn delay_before_call() {
+        let executor = MockHelmExecutor::new()
+            .with_rule(MockRule::any().delay(Duration::from_millis(20)))
+            .with_rule(MockRule::new(HelmMethod::List).delay(Duration::from_millis(20)));
+
+        let started = Instant::now();
+
+        executor.list(None).unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+}
+
+#[cfg(all(test, feature = "nonblocking-mock"))]
+mod nonblocking_mock_tests {
+    use std::time::Duration;
+
+    use crate::{
+        mock::{HelmMethod, MockHelmExecutor, MockRule},
+        nonblocking::HelmExecutor,
+    };
+
+    #[tokio::test]
+    async fn delayed_call_can_be_cancelled() {
+        let executor = MockHelmExecutor::new()
+            .with_rule(MockRule::new(HelmMethod::RepoList).delay(Duration::from_secs(10)));
+
+        let result = tokio::time::timeout(Duration::from_millis(20), executor.repo_list()).await;
+
+        assert!(result.is_err());
+        assert_eq!(executor.calls_of(HelmMethod::RepoList).len(), 1);
+    }
+}
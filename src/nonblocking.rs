@@ -1,9 +1,22 @@
-use std::{collections::HashMap, future::Future, path::Path, process::Command};
+use std::{ffi::OsString, future::Future, path::Path};
 
-use log::{debug, error, info, trace};
+use log::{debug, error, info};
 use non_blank_string_rs::NonBlankString;
 
-use crate::{error::HelmWrapperError, HelmDeployStatus, HelmListItem, HelmUpgradeResponse};
+use crate::{
+    command::{self, GetSubject, HelmCommand, HelmSettings},
+    error::HelmWrapperError,
+    lint::LintReport,
+    manifest::{parse_manifest, ManifestDocument},
+    redact::Redactor,
+    registry::{parse_pushed_chart, PushedChart, RegistryLoginOptions},
+    repo::{HelmRepository, RepoAddOptions, RepoCredentials},
+    request::{validate_values, InstallOrUpgradeRequest, ValuesSource},
+    runner::{AsyncCommandRunner, SystemCommandRunner},
+    search::{self, ChartSearchResult},
+    HelmDeployStatus, HelmListItem, HelmRelease, HelmReleaseMetadata, HelmReleaseStatus,
+    HelmRevision, RollbackOptions,
+};
 
 pub trait HelmExecutor: Send + Sync + Clone + 'static {
     /// List installed helm charts
@@ -19,19 +32,72 @@ pub trait HelmExecutor: Send + Sync + Clone + 'static {
     /// - `release_name` - release name. For example: myapp
     /// - `chart_name` - helm chart name. For example: cowboysysop/whoami
     /// - `chart_version` - helm chart version. For example: 1.2.3 (optional)
-    /// - `values_overrides` - values overrides, pass to helm as --set NAME=VALUE (optional)
+    /// - `values_overrides` - values overrides, pass to helm as --set NAME=VALUE in the given order (optional)
     /// - `values-file` - path to values file (optional)
     /// - `helm_options` - any other options for helm. for example '--dry-run' (optional)
+    ///
+    /// Shortcut for `install_or_upgrade_with`, use `InstallOrUpgradeRequest` for other options.
+    #[allow(clippy::too_many_arguments)]
     fn install_or_upgrade(
         &self,
         namespace: &NonBlankString,
         release_name: &NonBlankString,
         chart_name: &NonBlankString,
         chart_version: Option<&NonBlankString>,
-        values_overrides: Option<&HashMap<NonBlankString, String>>,
+        values_overrides: Option<&[(NonBlankString, String)]>,
         values_file: Option<&Path>,
         helm_options: Option<&Vec<NonBlankString>>,
-    ) -> impl Future<Output = Result<HelmDeployStatus, HelmWrapperError>> + Send;
+    ) -> impl Future<Output = Result<HelmDeployStatus, HelmWrapperError>> + Send {
+        let request = InstallOrUpgradeRequest::from_args(
+            namespace,
+            release_name,
+            chart_name,
+            chart_version,
+            values_overrides,
+            values_file,
+            helm_options,
+        );
+
+        async move {
+            let release = self.install_or_upgrade_with(&request).await?;
+            Ok(release.info.status)
+        }
+    }
+
+    /// Same as `install_or_upgrade`, but returns the whole release object: revision,
+    /// rendered manifest, notes, chart metadata and values.
+    #[allow(clippy::too_many_arguments)]
+    fn install_or_upgrade_release(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        chart_name: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        values_overrides: Option<&[(NonBlankString, String)]>,
+        values_file: Option<&Path>,
+        helm_options: Option<&Vec<NonBlankString>>,
+    ) -> impl Future<Output = Result<HelmRelease, HelmWrapperError>> + Send {
+        let request = InstallOrUpgradeRequest::from_args(
+            namespace,
+            release_name,
+            chart_name,
+            chart_version,
+            values_overrides,
+            values_file,
+            helm_options,
+        );
+
+        async move { self.install_or_upgrade_with(&request).await }
+    }
+
+    /// Install or upgrade helm chart with all options from request:
+    /// helm upgrade --install <RELEASE-NAME> <CHART-NAME> [OPTIONS]
+    ///
+    /// Returns the whole release object.
+    fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> impl Future<Output = Result<HelmRelease, HelmWrapperError>> + Send;
 
     /// - `helm_options` - any other options for helm. for example '--dry-run' (optional)
     fn uninstall(
@@ -39,10 +105,253 @@ pub trait HelmExecutor: Send + Sync + Clone + 'static {
         namespace: &NonBlankString,
         release_name: &NonBlankString,
     ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
+
+    /// Rollback release in such way:
+    /// helm rollback <RELEASE-NAME> <REVISION> [--wait] [--cleanup-on-fail] [--force] [--recreate-pods]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - target revision, previous revision if not specified (optional)
+    /// - `options` - rollback options
+    ///
+    /// Returns revision which release was rolled back to.
+    fn rollback(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        options: &RollbackOptions,
+    ) -> impl Future<Output = Result<u32, HelmWrapperError>> + Send;
+
+    /// Get release history in such way:
+    /// helm history <RELEASE-NAME> [--max MAX]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `max` - maximum number of revisions to return (optional)
+    fn history(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        max: Option<u32>,
+    ) -> impl Future<Output = Result<Vec<HelmRevision>, HelmWrapperError>> + Send;
+
+    /// Get release status in such way:
+    /// helm status <RELEASE-NAME> [--revision REVISION] --show-resources
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn status(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> impl Future<Output = Result<HelmReleaseStatus, HelmWrapperError>> + Send;
+
+    /// Get release values in such way:
+    /// helm get values <RELEASE-NAME> [--revision REVISION] [--all]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    /// - `all` - return all computed values instead of user supplied ones
+    fn get_values(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        all: bool,
+    ) -> impl Future<Output = Result<serde_json::Value, HelmWrapperError>> + Send;
+
+    /// Get release manifest split into Kubernetes documents:
+    /// helm get manifest <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_manifest(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> impl Future<Output = Result<Vec<ManifestDocument>, HelmWrapperError>> + Send;
+
+    /// Get release notes:
+    /// helm get notes <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_notes(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> impl Future<Output = Result<String, HelmWrapperError>> + Send;
+
+    /// Get release hooks split into Kubernetes documents:
+    /// helm get hooks <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_hooks(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> impl Future<Output = Result<Vec<ManifestDocument>, HelmWrapperError>> + Send;
+
+    /// Get release metadata:
+    /// helm get metadata <RELEASE-NAME> [--revision REVISION]
+    /// - `namespace` - release namespace
+    /// - `release_name` - release name. For example: myapp
+    /// - `revision` - release revision, latest if not specified (optional)
+    fn get_metadata(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> impl Future<Output = Result<HelmReleaseMetadata, HelmWrapperError>> + Send;
+
+    /// Render chart templates locally, without cluster:
+    /// helm template <RELEASE-NAME> <CHART-NAME> [OPTIONS]
+    ///
+    /// Namespace, release and chart names, chart version, values, overrides and helm options
+    /// are taken from request, install options (atomic, wait, labels etc.) are ignored.
+    /// Returns rendered documents.
+    fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> impl Future<Output = Result<Vec<ManifestDocument>, HelmWrapperError>> + Send;
+
+    /// Lint chart:
+    /// helm lint <CHART-PATH> [-f VALUES]... [--strict]
+    /// - `chart_path` - path to chart directory or archive
+    /// - `values` - values sources in precedence order
+    /// - `strict` - fail on warnings
+    ///
+    /// Failed lint is not an error, check `LintReport::is_success`.
+    fn lint(
+        &self,
+        chart_path: &Path,
+        values: &[ValuesSource],
+        strict: bool,
+    ) -> impl Future<Output = Result<LintReport, HelmWrapperError>> + Send;
+
+    /// Add chart repository:
+    /// helm repo add <NAME> <URL> [OPTIONS]
+    /// - `name` - repository name. For example: cowboysysop
+    /// - `url` - repository url
+    /// - `options` - credentials, CA file, TLS and update options
+    ///
+    /// Password is passed through stdin and masked in logs and errors.
+    fn repo_add(
+        &self,
+        name: &NonBlankString,
+        url: &NonBlankString,
+        options: &RepoAddOptions,
+    ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
+
+    /// Update information of available charts:
+    /// helm repo update [NAME]...
+    /// - `names` - repositories to update, all if empty
+    fn repo_update(
+        &self,
+        names: &[NonBlankString],
+    ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
+
+    /// List chart repositories, empty if there are no repositories
+    fn repo_list(
+        &self,
+    ) -> impl Future<Output = Result<Vec<HelmRepository>, HelmWrapperError>> + Send;
+
+    /// Remove chart repository:
+    /// helm repo remove <NAME>
+    fn repo_remove(
+        &self,
+        name: &NonBlankString,
+    ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
+
+    /// Search charts in added repositories:
+    /// helm search repo <KEYWORD> [--versions] [--devel] [--version CONSTRAINT]
+    /// - `keyword` - chart name or part of it. For example: whoami
+    /// - `versions` - all versions of each chart, otherwise the latest one
+    /// - `devel` - include development versions (alpha, beta, release candidates)
+    /// - `version_constraint` - semver constraint. For example: ^5.0.0 (optional)
+    fn search_repo(
+        &self,
+        keyword: &NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&NonBlankString>,
+    ) -> impl Future<Output = Result<Vec<ChartSearchResult>, HelmWrapperError>> + Send;
+
+    /// Stable versions of release chart newer than the deployed one, newest first.
+    /// Searches all added repositories, so the same version may be found in several of them.
+    /// Empty if release chart version can't be determined.
+    fn newer_chart_versions(
+        &self,
+        release: &HelmListItem,
+    ) -> impl Future<Output = Result<Vec<ChartSearchResult>, HelmWrapperError>> + Send {
+        let keyword = search::chart_keyword(release);
+        let release = release.clone();
+
+        async move {
+            let Some(keyword) = keyword else {
+                return Ok(vec![]);
+            };
+
+            let results = self.search_repo(&keyword, true, false, None).await?;
+
+            Ok(search::newer_versions(&release, &results))
+        }
+    }
+
+    /// Log in to OCI registry:
+    /// helm registry login <HOST> --username USERNAME --password-stdin [OPTIONS]
+    /// - `host` - registry host. For example: ghcr.io
+    /// - `credentials` - username and password, password is passed through stdin
+    /// - `options` - TLS options
+    fn registry_login(
+        &self,
+        host: &NonBlankString,
+        credentials: &RepoCredentials,
+        options: &RegistryLoginOptions,
+    ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
+
+    /// Log out from OCI registry:
+    /// helm registry logout <HOST>
+    fn registry_logout(
+        &self,
+        host: &NonBlankString,
+    ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
+
+    /// Push packaged chart to OCI registry:
+    /// helm push <CHART-PACKAGE> <REMOTE>
+    /// - `chart_package` - chart archive. For example: whoami-5.2.0.tgz
+    /// - `remote` - registry path. For example: oci://ghcr.io/charts
+    fn push(
+        &self,
+        chart_package: &Path,
+        remote: &NonBlankString,
+    ) -> impl Future<Output = Result<PushedChart, HelmWrapperError>> + Send;
+
+    /// Download chart archive to directory:
+    /// helm pull <CHART> [--version VERSION] --destination <DIR>
+    /// - `chart` - chart reference. For example: cowboysysop/whoami or oci://ghcr.io/charts/whoami
+    /// - `chart_version` - chart version, the latest if not specified (optional)
+    /// - `destination` - target directory
+    fn pull(
+        &self,
+        chart: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        destination: &Path,
+    ) -> impl Future<Output = Result<(), HelmWrapperError>> + Send;
 }
 
+/// Helm executor, runs helm processes with `R` runner.
+///
+/// Default `SystemCommandRunner` is based on `tokio::process`: helm processes are
+/// killed when the returned future is dropped, so cancelling a call
+/// (e.g. with `tokio::time::timeout`) stops the underlying helm command.
+/// Use `with_runner` to replace it, e.g. with `ScriptedCommandRunner` in unit tests.
 #[derive(Clone, Debug)]
-pub struct DefaultHelmExecutor(String, Option<String>, u16, bool, bool);
+pub struct DefaultHelmExecutor<R = SystemCommandRunner>(HelmSettings, R);
 
 impl DefaultHelmExecutor {
     /// Create executor instance with predefined option values:
@@ -52,7 +361,7 @@ impl DefaultHelmExecutor {
     /// - Debug: false
     /// - unsafe_mode: false - print overridden values to log
     pub fn new() -> Self {
-        Self("helm".to_string(), None, 15, false, false)
+        Self(HelmSettings::default(), SystemCommandRunner)
     }
 
     /// Create execute with options:
@@ -69,316 +378,688 @@ impl DefaultHelmExecutor {
         unsafe_mode: bool,
     ) -> Self {
         Self(
-            helm_path.to_string(),
-            kubeconfig_path,
-            timeout,
-            debug,
-            unsafe_mode,
+            HelmSettings {
+                helm_path: helm_path.to_string(),
+                kubeconfig_path,
+                timeout,
+                debug,
+                unsafe_mode,
+                redactor: Redactor::default(),
+                env: vec![],
+            },
+            SystemCommandRunner,
         )
     }
+}
+
+impl<R> DefaultHelmExecutor<R> {
+    /// Replace process runner, settings are kept
+    pub fn with_runner<T>(self, runner: T) -> DefaultHelmExecutor<T> {
+        DefaultHelmExecutor(self.0, runner)
+    }
+
+    pub fn get_runner(&self) -> &R {
+        &self.1
+    }
+
+    /// Set redactor which scrubs sensitive values from logs and error payloads:
+    /// command args, helm output and `HelmCommandFailure` stdout/stderr
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.0.redactor = redactor;
+        self
+    }
+
+    /// Set environment variable for helm process in addition to inherited ones,
+    /// e.g. `HELM_REPOSITORY_CONFIG` or `HELM_CACHE_HOME` to isolate helm state
+    pub fn with_env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.0.env.push((key.into(), value.into()));
+        self
+    }
 
     pub fn get_helm_path(&self) -> &str {
-        &self.0
+        &self.0.helm_path
     }
 
     pub fn get_kubeconfig_path(&self) -> &Option<String> {
-        &self.1
+        &self.0.kubeconfig_path
     }
 
     pub fn get_timeout(&self) -> u16 {
-        self.2
+        self.0.timeout
     }
 
     pub fn get_debug(&self) -> bool {
-        self.3
+        self.0.debug
     }
 
     pub fn get_unsafe_mode(&self) -> bool {
-        self.4
+        self.0.unsafe_mode
     }
 
-    fn remove_double_spaces_and_trim(&self, input: &str) -> String {
-        let result = input.replace("  ", " ");
-        result.trim().to_string()
+    pub fn get_redactor(&self) -> &Redactor {
+        &self.0.redactor
     }
 }
 
-impl HelmExecutor for DefaultHelmExecutor {
+impl<R: AsyncCommandRunner> DefaultHelmExecutor<R> {
+    /// Run helm with given arguments and return its stdout
+    async fn execute(&self, command: HelmCommand) -> Result<String, HelmWrapperError> {
+        if self.get_unsafe_mode() {
+            debug!(
+                "command args: '{}'",
+                command.args.redacted(&self.0.redactor)
+            )
+        }
+
+        match self.1.run(&command.invocation(&self.0)).await {
+            Ok(output) => command.read_output(&self.0, output),
+            Err(e) => {
+                error!("helm execution error: {}", e);
+                Err(HelmWrapperError::ExecutionError(e))
+            }
+        }
+    }
+}
+
+/// Build command on blocking thread: values files are checked and in-memory values
+/// are written to temp files, both hit filesystem
+async fn prepare(
+    build: impl FnOnce() -> Result<HelmCommand, HelmWrapperError> + Send + 'static,
+) -> Result<HelmCommand, HelmWrapperError> {
+    tokio::task::spawn_blocking(build)
+        .await
+        .map_err(|e| HelmWrapperError::ExecutionError(std::io::Error::other(e)))?
+}
+
+impl Default for DefaultHelmExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: AsyncCommandRunner + Clone + 'static> HelmExecutor for DefaultHelmExecutor<R> {
     async fn list(
         &self,
         namespace: Option<&NonBlankString>,
     ) -> Result<Vec<HelmListItem>, HelmWrapperError> {
         info!("get list of installed helm charts..");
 
-        debug!("helm executable path '{}'", self.get_helm_path());
-        debug!("kubeconfig file path '{:?}'", self.get_kubeconfig_path());
-        debug!("timeout {}s", self.get_timeout());
+        self.0.log();
 
-        let mut command_args = format!("ls");
+        let stdout = self
+            .execute(command::list_command(&self.0, namespace))
+            .await?;
 
-        if let Some(namespace) = namespace {
-            info!("- namespace '{namespace}'");
-            command_args.push_str(&format!(" -n {} -o json ", namespace));
-        }
+        let helm_response: Vec<HelmListItem> = serde_json::from_str(&stdout)?;
 
-        match &self.1 {
-            Some(kubeconfig_path) => {
-                info!("- kubeconfig path '{}'", kubeconfig_path);
-                command_args.push_str(&format!(" --kubeconfig {} ", kubeconfig_path));
-            }
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
+        );
+
+        Ok(helm_response)
+    }
+
+    async fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        info!(
+            "installing helm chart '{}' with release name '{}' to namespace '{}'..",
+            request.chart_name, request.release_name, request.namespace
+        );
+
+        self.0.log();
+
+        let settings = self.0.clone();
+        let request = request.clone();
+
+        let command = prepare(move || {
+            request.validate()?;
+            command::install_or_upgrade_command(&settings, &request)
+        })
+        .await?;
+
+        let stdout = self.execute(command).await?;
+
+        let helm_response: HelmRelease = serde_json::from_str(&stdout)?;
+
+        info!(
+            "release '{}' revision {} status '{}'",
+            helm_response.name, helm_response.version, helm_response.info.status
+        );
+
+        Ok(helm_response)
+    }
+
+    async fn uninstall(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        info!(
+            "uninstalling helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
+
+        self.execute(command::uninstall_command(&self.0, namespace, release_name))
+            .await?;
+
+        info!("helm release '{}' uninstalled successfully", release_name);
+
+        Ok(())
+    }
+
+    async fn rollback(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        info!(
+            "rolling back helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
+
+        self.0.log();
+
+        let revision = match revision {
+            Some(revision) => revision,
             None => {
-                trace!("no kubeconfig path provided");
+                let stdout = self
+                    .execute(command::release_list_command(
+                        &self.0,
+                        namespace,
+                        release_name,
+                    ))
+                    .await?;
+
+                let releases: Vec<HelmListItem> = serde_json::from_str(&stdout)?;
+
+                command::previous_revision(release_name, &releases)?
             }
-        }
+        };
 
-        if self.get_debug() {
-            command_args.push_str(" --debug ");
-        }
+        info!("- revision {revision}");
 
-        command_args = self.remove_double_spaces_and_trim(&command_args);
+        self.execute(command::rollback_command(
+            &self.0,
+            namespace,
+            release_name,
+            revision,
+            options,
+        ))
+        .await?;
 
-        let command_args: Vec<&str> = command_args.split(" ").collect();
+        info!(
+            "helm release '{}' rolled back to revision {}",
+            release_name, revision
+        );
 
-        match Command::new(&self.get_helm_path())
-            .args(command_args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8(output.stdout)?;
+        Ok(revision)
+    }
 
-                    if self.get_unsafe_mode() {
-                        debug!("<stdout>");
-                        debug!("{}", stdout);
-                        debug!("</stdout>");
-                    }
+    async fn history(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        info!(
+            "get history of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-                    let helm_response: Vec<HelmListItem> = serde_json::from_str(&stdout)?;
+        self.0.log();
 
-                    info!("response: {:?}", helm_response);
+        let stdout = self
+            .execute(command::history_command(
+                &self.0,
+                namespace,
+                release_name,
+                max,
+            ))
+            .await?;
 
-                    Ok(helm_response)
-                } else {
-                    error!("command execution error");
-                    let stderr = String::from_utf8_lossy(&output.stderr);
+        let helm_response: Vec<HelmRevision> = serde_json::from_str(&stdout)?;
 
-                    error!("<stderr>");
-                    error!("{}", stderr);
-                    error!("</stderr>");
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
+        );
 
-                    Err(HelmWrapperError::Error)
-                }
-            }
-            Err(e) => {
-                error!("execution error: {}", e);
-                Err(HelmWrapperError::ExecutionError(e))
-            }
-        }
+        Ok(helm_response)
     }
 
-    async fn install_or_upgrade(
+    async fn status(
         &self,
         namespace: &NonBlankString,
         release_name: &NonBlankString,
-        chart_name: &NonBlankString,
-        chart_version: Option<&NonBlankString>,
-        values_overrides: Option<&HashMap<NonBlankString, String>>,
-        values_file: Option<&Path>,
-        helm_options: Option<&Vec<NonBlankString>>,
-    ) -> Result<HelmDeployStatus, HelmWrapperError> {
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
         info!(
-            "installing helm chart '{}' with release name '{}' to namespace '{}'..",
-            chart_name, release_name, namespace
+            "get status of helm release '{}', namespace '{}'..",
+            release_name, namespace
         );
 
-        debug!("helm executable path '{}'", self.get_helm_path());
-        debug!("kubeconfig file path '{:?}'", self.get_kubeconfig_path());
-        debug!("timeout {}s", self.get_timeout());
+        self.0.log();
 
-        let mut command_args = format!(
-            "upgrade --install -n {} {} {}",
-            namespace, release_name, chart_name
+        let stdout = self
+            .execute(command::status_command(
+                &self.0,
+                namespace,
+                release_name,
+                revision,
+            ))
+            .await?;
+
+        let helm_response: HelmReleaseStatus = serde_json::from_str(&stdout)?;
+
+        info!(
+            "release '{}' revision {} status '{}'",
+            helm_response.name, helm_response.revision, helm_response.info.status
         );
 
-        match &self.1 {
-            Some(kubeconfig_path) => {
-                info!("- kubeconfig path '{}'", kubeconfig_path);
-                command_args.push_str(&format!(" --kubeconfig={} ", kubeconfig_path));
-            }
-            None => {
-                debug!("no kubeconfig path provided");
-            }
-        }
+        Ok(helm_response)
+    }
 
-        if let Some(chart_version) = chart_version {
-            info!("- chart version '{chart_version}'");
-            command_args.push_str(&format!(" --version {} ", chart_version));
-        }
+    async fn get_values(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+        all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        info!(
+            "get values of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-        if let Some(values_file) = values_file {
-            info!("- values file '{}'", values_file.display());
-            command_args.push_str(&format!(" -f {} ", values_file.display()));
-        }
+        self.0.log();
 
-        if let Some(overrides) = values_overrides {
-            if !self.get_unsafe_mode() {
-                info!("overriden chart values won't be mentioned in log because of safe mode");
-            }
+        let stdout = self
+            .execute(command::get_command(
+                &self.0,
+                GetSubject::Values { all },
+                namespace,
+                release_name,
+                revision,
+            ))
+            .await?;
 
-            for (k, v) in overrides.iter() {
-                if self.get_unsafe_mode() {
-                    info!("- value override '{}': '{}'", k, v);
-                }
-                command_args.push_str(&format!(" --set {}={} ", k, v));
-            }
-        }
+        let helm_response: serde_json::Value = serde_json::from_str(&stdout)?;
 
-        if let Some(helm_options) = helm_options {
-            for helm_option in helm_options {
-                info!("- helm option '{helm_option}'");
-                command_args.push_str(&format!(" {helm_option} "));
-            }
-        }
+        Ok(helm_response)
+    }
 
-        if self.get_debug() {
-            command_args.push_str(" --debug ");
-        }
+    async fn get_manifest(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        info!(
+            "get manifest of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-        command_args.push_str(&format!(" -o json --timeout {}s ", self.get_timeout()));
+        self.0.log();
 
-        let command_args = command_args.replace("  ", " ");
-        let command_args = command_args.trim();
+        let stdout = self
+            .execute(command::get_command(
+                &self.0,
+                GetSubject::Manifest,
+                namespace,
+                release_name,
+                revision,
+            ))
+            .await?;
 
-        if self.get_unsafe_mode() {
-            debug!("command args: '{command_args}'")
-        }
+        let helm_response = parse_manifest(&stdout);
 
-        let command_args: Vec<&str> = command_args.split(" ").collect();
+        Ok(helm_response)
+    }
 
-        match Command::new(&self.get_helm_path())
-            .args(command_args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8(output.stdout)?;
+    async fn get_notes(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        info!(
+            "get notes of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-                    if self.get_unsafe_mode() {
-                        debug!("<stdout>");
-                        debug!("{}", stdout);
-                        debug!("</stdout>");
-                    }
+        self.0.log();
 
-                    let helm_response: HelmUpgradeResponse = serde_json::from_str(&stdout)?;
+        let stdout = self
+            .execute(command::get_command(
+                &self.0,
+                GetSubject::Notes,
+                namespace,
+                release_name,
+                revision,
+            ))
+            .await?;
 
-                    info!("response: {:?}", helm_response);
+        let helm_response = command::parse_notes(&stdout);
 
-                    Ok(helm_response.info.status)
-                } else {
-                    error!("helm command execution error");
-                    let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(helm_response)
+    }
 
-                    error!("<stderr>");
-                    error!("{}", stderr);
-                    error!("</stderr>");
+    async fn get_hooks(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        info!(
+            "get hooks of helm release '{}', namespace '{}'..",
+            release_name, namespace
+        );
 
-                    Err(HelmWrapperError::Error)
-                }
-            }
-            Err(e) => {
-                error!("helm execution error: {}", e);
-                Err(HelmWrapperError::ExecutionError(e))
-            }
-        }
+        self.0.log();
+
+        let stdout = self
+            .execute(command::get_command(
+                &self.0,
+                GetSubject::Hooks,
+                namespace,
+                release_name,
+                revision,
+            ))
+            .await?;
+
+        let helm_response = parse_manifest(&stdout);
+
+        Ok(helm_response)
     }
 
-    async fn uninstall(
+    async fn get_metadata(
         &self,
         namespace: &NonBlankString,
         release_name: &NonBlankString,
-    ) -> Result<(), HelmWrapperError> {
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
         info!(
-            "uninstalling helm release '{}', namespace '{}'..",
+            "get metadata of helm release '{}', namespace '{}'..",
             release_name, namespace
         );
 
-        let mut command_args = format!(
-            "uninstall {} -n {} --timeout={}s --wait",
-            release_name,
-            namespace,
-            self.get_timeout()
+        self.0.log();
+
+        let stdout = self
+            .execute(command::get_command(
+                &self.0,
+                GetSubject::Metadata,
+                namespace,
+                release_name,
+                revision,
+            ))
+            .await?;
+
+        let helm_response: HelmReleaseMetadata = serde_json::from_str(&stdout)?;
+
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
         );
 
-        if self.get_debug() {
-            command_args.push_str(" --debug ");
-        }
+        Ok(helm_response)
+    }
 
-        match &self.1 {
-            Some(kubeconfig_path) => {
-                info!("- kubeconfig path '{}'", kubeconfig_path);
-                command_args.push_str(&format!(" --kubeconfig={} ", kubeconfig_path));
-            }
-            None => {
-                debug!("no kubeconfig path provided");
-            }
-        }
+    async fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        info!(
+            "rendering helm chart '{}' with release name '{}', namespace '{}'..",
+            request.chart_name, request.release_name, request.namespace
+        );
 
-        let command_args = command_args.replace("  ", " ").to_string();
-        let command_args = command_args.trim();
+        self.0.log();
 
-        if self.get_unsafe_mode() {
-            debug!("command args: '{command_args}'")
-        }
+        let settings = self.0.clone();
+        let request = request.clone();
 
-        let command_args: Vec<&str> = command_args.split(" ").collect();
+        let command = prepare(move || {
+            request.validate()?;
+            command::template_command(&settings, &request)
+        })
+        .await?;
 
-        match Command::new(&self.get_helm_path())
-            .args(command_args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    let stdout = String::from_utf8(output.stdout)?;
+        let stdout = self.execute(command).await?;
 
-                    if self.get_unsafe_mode() {
-                        debug!("<stdout>");
-                        debug!("{}", stdout);
-                        debug!("</stdout>");
-                    }
+        let helm_response = parse_manifest(&stdout);
 
-                    info!("helm release '{}' uninstalled successfully", release_name);
+        info!("{} documents rendered", helm_response.len());
 
-                    Ok(())
-                } else {
-                    error!("helm command execution error");
-                    let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(helm_response)
+    }
 
-                    error!("<stderr>");
-                    error!("{}", stderr);
-                    error!("</stderr>");
+    async fn lint(
+        &self,
+        chart_path: &Path,
+        values: &[ValuesSource],
+        strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        info!("linting helm chart '{}'..", chart_path.display());
 
-                    Err(HelmWrapperError::Error)
-                }
-            }
-            Err(e) => {
-                error!("helm execution error: {}", e);
-                Err(HelmWrapperError::ExecutionError(e))
-            }
-        }
+        self.0.log();
+
+        let settings = self.0.clone();
+        let chart_path = chart_path.to_path_buf();
+        let values = values.to_vec();
+
+        let command = prepare(move || {
+            validate_values(&values)?;
+            command::lint_command(&settings, &chart_path, &values, strict)
+        })
+        .await?;
+
+        let result = self.execute(command).await;
+
+        let helm_response = command::read_lint_report(result)?;
+
+        info!(
+            "{} chart(s) linted, {} chart(s) failed",
+            helm_response.linted, helm_response.failed
+        );
+
+        Ok(helm_response)
+    }
+
+    async fn repo_add(
+        &self,
+        name: &NonBlankString,
+        url: &NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        info!(
+            "adding helm repository '{}', url '{}'..",
+            name,
+            self.0.redactor.redact_text(url)
+        );
+
+        self.0.log();
+
+        self.execute(command::repo_add_command(&self.0, name, url, options))
+            .await?;
+
+        info!("helm repository '{}' added", name);
+
+        Ok(())
+    }
+
+    async fn repo_update(&self, names: &[NonBlankString]) -> Result<(), HelmWrapperError> {
+        info!("updating helm repositories..");
+
+        self.0.log();
+
+        self.execute(command::repo_update_command(&self.0, names))
+            .await?;
+
+        info!("helm repositories updated");
+
+        Ok(())
+    }
+
+    async fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        info!("get list of helm repositories..");
+
+        self.0.log();
+
+        let result = self.execute(command::repo_list_command(&self.0)).await;
+
+        let helm_response = command::read_repo_list(result)?;
+
+        info!(
+            "response: {}",
+            self.0.redactor.redact_text(&format!("{helm_response:?}"))
+        );
+
+        Ok(helm_response)
+    }
+
+    async fn repo_remove(&self, name: &NonBlankString) -> Result<(), HelmWrapperError> {
+        info!("removing helm repository '{}'..", name);
+
+        self.0.log();
+
+        self.execute(command::repo_remove_command(&self.0, name))
+            .await?;
+
+        info!("helm repository '{}' removed", name);
+
+        Ok(())
+    }
+
+    async fn search_repo(
+        &self,
+        keyword: &NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        info!("searching helm charts by keyword '{}'..", keyword);
+
+        self.0.log();
+
+        let stdout = self
+            .execute(command::search_repo_command(
+                &self.0,
+                keyword,
+                versions,
+                devel,
+                version_constraint,
+            ))
+            .await?;
+
+        let helm_response: Vec<ChartSearchResult> = serde_json::from_str(&stdout)?;
+
+        info!("{} chart version(s) found", helm_response.len());
+
+        Ok(helm_response)
+    }
+
+    async fn registry_login(
+        &self,
+        host: &NonBlankString,
+        credentials: &RepoCredentials,
+        options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        info!("logging in to registry '{}'..", host);
+
+        self.0.log();
+
+        self.execute(command::registry_login_command(
+            &self.0,
+            host,
+            credentials,
+            options,
+        ))
+        .await?;
+
+        info!("logged in to registry '{}'", host);
+
+        Ok(())
+    }
+
+    async fn registry_logout(&self, host: &NonBlankString) -> Result<(), HelmWrapperError> {
+        info!("logging out from registry '{}'..", host);
+
+        self.0.log();
+
+        self.execute(command::registry_logout_command(&self.0, host))
+            .await?;
+
+        info!("logged out from registry '{}'", host);
+
+        Ok(())
+    }
+
+    async fn push(
+        &self,
+        chart_package: &Path,
+        remote: &NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        info!(
+            "pushing chart '{}' to '{}'..",
+            chart_package.display(),
+            remote
+        );
+
+        self.0.log();
+
+        let stdout = self
+            .execute(command::push_command(&self.0, chart_package, remote))
+            .await?;
+
+        let helm_response = parse_pushed_chart(&stdout);
+
+        info!("response: {:?}", helm_response);
+
+        Ok(helm_response)
+    }
+
+    async fn pull(
+        &self,
+        chart: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        destination: &Path,
+    ) -> Result<(), HelmWrapperError> {
+        info!("pulling chart '{}'..", chart);
+
+        self.0.log();
+
+        self.execute(command::pull_command(
+            &self.0,
+            chart,
+            chart_version,
+            destination,
+        ))
+        .await?;
+
+        info!("chart '{}' pulled to '{}'", chart, destination.display());
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod nonblocking_helm_command_tests {
-    use std::{collections::HashMap, path::Path};
+    use std::path::Path;
 
     use non_blank_string_rs::NonBlankString;
 
     use crate::{
         nonblocking::{DefaultHelmExecutor, HelmExecutor},
+        repo::RepoAddOptions,
         tests::{
-            get_test_chart_name, get_test_helm_options, get_test_namespace, get_test_release_name,
-            init_logging,
+            get_test_chart_name, get_test_helm_options, get_test_helm_path, get_test_namespace,
+            get_test_release_name, get_test_repo_name, get_test_repo_url, init_logging,
         },
         HelmDeployStatus,
     };
@@ -388,7 +1069,7 @@ mod nonblocking_helm_command_tests {
         init_logging();
 
         let executor =
-            DefaultHelmExecutor::new_with_opts(&"helm".parse().unwrap(), None, 15, true, true);
+            DefaultHelmExecutor::new_with_opts(&get_test_helm_path(), None, 15, true, true);
 
         let helm_options: Vec<NonBlankString> = get_test_helm_options();
 
@@ -417,21 +1098,30 @@ mod nonblocking_helm_command_tests {
         init_logging();
 
         let executor =
-            DefaultHelmExecutor::new_with_opts(&"helm".parse().unwrap(), None, 15, true, true);
+            DefaultHelmExecutor::new_with_opts(&get_test_helm_path(), None, 15, true, true);
+
+        executor
+            .repo_add(
+                &get_test_repo_name(),
+                &get_test_repo_url(),
+                &RepoAddOptions {
+                    force_update: true,
+                    ..RepoAddOptions::default()
+                },
+            )
+            .await
+            .unwrap();
+        executor.repo_update(&[get_test_repo_name()]).await.unwrap();
 
         let helm_options: Vec<NonBlankString> = get_test_helm_options();
 
         let namespace: NonBlankString = get_test_namespace();
         let release_name: NonBlankString = get_test_release_name();
         let chart_name: NonBlankString = get_test_chart_name();
-
-        let mut values_overrides: HashMap<NonBlankString, String> = HashMap::new();
-
-        values_overrides.insert(
-            "startupProbe.enabled".parse().unwrap(),
-            "false".parse().unwrap(),
-        );
-        values_overrides.insert("replicaCount".parse().unwrap(), "2".parse().unwrap());
+        let values_overrides: Vec<(NonBlankString, String)> = vec![
+            ("startupProbe.enabled".parse().unwrap(), "false".to_string()),
+            ("replicaCount".parse().unwrap(), "2".to_string()),
+        ];
 
         let values_file = Path::new("test-data").join("whoami-values.yml");
 
@@ -441,7 +1131,7 @@ mod nonblocking_helm_command_tests {
                 &release_name,
                 &chart_name,
                 Some(&"5.2.0".parse().unwrap()),
-                Some(&values_overrides),
+                Some(values_overrides.as_slice()),
                 Some(&values_file),
                 Some(&helm_options),
             )
@@ -468,3 +1158,114 @@ mod nonblocking_helm_command_tests {
         assert!(releases.is_empty());
     }
 }
+
+#[cfg(test)]
+mod nonblocking_executor_tests {
+    use std::io;
+
+    use crate::{
+        error::HelmWrapperError,
+        nonblocking::{DefaultHelmExecutor, HelmExecutor},
+        repo::{RepoAddOptions, RepoCredentials},
+        runner::{CommandOutput, ScriptedCommandRunner},
+        HelmDeployStatus,
+    };
+
+    fn executor(runner: &ScriptedCommandRunner) -> DefaultHelmExecutor<ScriptedCommandRunner> {
+        DefaultHelmExecutor::new_with_opts(
+            &"/usr/bin/helm".parse().unwrap(),
+            None,
+            15,
+            false,
+            false,
+        )
+        .with_runner(runner.clone())
+    }
+
+    #[tokio::test]
+    async fn list_is_parsed_from_runner_output() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::ok(
+            r#"[{"name":"whoami","namespace":"whoami","revision":"1","updated":"2024-01-01 00:00:00","status":"deployed","chart":"whoami-5.2.0","app_version":"1.10.3"}]"#,
+        ));
+
+        let releases = executor(&runner)
+            .list(Some(&"whoami".parse().unwrap()))
+            .await
+            .unwrap();
+
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].status, HelmDeployStatus::Deployed);
+        assert_eq!(releases[0].app_version, "1.10.3");
+
+        let invocations = runner.invocations();
+
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].program, "/usr/bin/helm");
+        assert_eq!(
+            invocations[0].args_lossy(),
+            vec!["ls", "-n", "whoami", "-o", "json"]
+        );
+        assert_eq!(invocations[0].stdin, None);
+    }
+
+    #[tokio::test]
+    async fn failed_command_is_classified() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::failure(
+            1,
+            "Error: uninstall: Release not loaded: whoami: release: not found",
+        ));
+
+        let error = executor(&runner)
+            .uninstall(&"whoami".parse().unwrap(), &"whoami".parse().unwrap())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, HelmWrapperError::ReleaseNotFound(_)));
+
+        let failure = error.failure().unwrap();
+
+        assert_eq!(failure.subcommand, "uninstall");
+        assert_eq!(failure.release_name.as_deref(), Some("whoami"));
+        assert_eq!(failure.exit_code, Some(1));
+    }
+
+    #[tokio::test]
+    async fn runner_error_is_execution_error() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_error(io::Error::new(io::ErrorKind::NotFound, "helm not found"));
+
+        let error = executor(&runner).list(None).await.unwrap_err();
+
+        assert!(matches!(error, HelmWrapperError::ExecutionError(_)));
+    }
+
+    #[tokio::test]
+    async fn repo_password_is_written_to_stdin() {
+        let runner = ScriptedCommandRunner::new();
+        runner.push_output(CommandOutput::ok("\"private\" has been added\n"));
+
+        let options = RepoAddOptions {
+            credentials: Some(RepoCredentials::new("admin", "p4ss")),
+            ..RepoAddOptions::default()
+        };
+
+        executor(&runner)
+            .repo_add(
+                &"private".parse().unwrap(),
+                &"https://charts.example.com".parse().unwrap(),
+                &options,
+            )
+            .await
+            .unwrap();
+
+        let invocation = runner.invocations().remove(0);
+
+        assert_eq!(invocation.stdin.as_deref(), Some("p4ss"));
+        assert!(!invocation
+            .args_lossy()
+            .iter()
+            .any(|arg| arg.contains("p4ss")));
+    }
+}
@@ -1,4 +1,17 @@
-use crate::{error::HelmWrapperError, nonblocking::HelmExecutor, HelmDeployStatus, HelmListItem};
+use crate::{
+    error::HelmWrapperError,
+    lint::{ChartLintResult, LintReport},
+    manifest::ManifestDocument,
+    mock::{HelmCall, MockHelmExecutor},
+    nonblocking::HelmExecutor,
+    registry::{PushedChart, RegistryLoginOptions},
+    repo::{HelmRepository, RepoAddOptions, RepoCredentials},
+    request::{InstallOrUpgradeRequest, ValuesSource},
+    search::ChartSearchResult,
+    simulator::SimulatorHelmExecutor,
+    HelmDeployStatus, HelmListItem, HelmRelease, HelmReleaseInfo, HelmReleaseMetadata,
+    HelmReleaseStatus, HelmRevision, RollbackOptions,
+};
 
 #[derive(Clone)]
 pub struct SuccessMockHelmExecutor(Vec<HelmListItem>, HelmDeployStatus);
@@ -20,26 +33,846 @@ impl HelmExecutor for SuccessMockHelmExecutor {
         Ok(self.0.clone())
     }
 
-    async fn install_or_upgrade(
+    async fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        Ok(HelmRelease {
+            name: request.release_name.to_string(),
+            namespace: request.namespace.to_string(),
+            version: 1,
+            info: HelmReleaseInfo {
+                status: self.1.clone(),
+                ..HelmReleaseInfo::default()
+            },
+            ..HelmRelease::default()
+        })
+    }
+
+    async fn uninstall(
         &self,
         _namespace: &non_blank_string_rs::NonBlankString,
         _release_name: &non_blank_string_rs::NonBlankString,
-        _chart_name: &non_blank_string_rs::NonBlankString,
-        _chart_version: Option<&non_blank_string_rs::NonBlankString>,
-        _values_overrides: Option<
-            &std::collections::HashMap<non_blank_string_rs::NonBlankString, String>,
-        >,
-        _values_file: Option<&std::path::Path>,
-        _helm_options: Option<&Vec<non_blank_string_rs::NonBlankString>>,
-    ) -> Result<HelmDeployStatus, HelmWrapperError> {
-        Ok(self.1.clone())
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
     }
 
-    async fn uninstall(
+    async fn rollback(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        _options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        Ok(revision.unwrap_or(1))
+    }
+
+    async fn history(
         &self,
         _namespace: &non_blank_string_rs::NonBlankString,
         _release_name: &non_blank_string_rs::NonBlankString,
+        _max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    async fn status(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        Ok(HelmReleaseStatus {
+            name: release_name.to_string(),
+            namespace: namespace.to_string(),
+            revision: revision.unwrap_or(1),
+            info: HelmReleaseInfo {
+                status: self.1.clone(),
+                ..HelmReleaseInfo::default()
+            },
+            ..HelmReleaseStatus::default()
+        })
+    }
+
+    async fn get_values(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+        _all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        Ok(serde_json::Value::Null)
+    }
+
+    async fn get_manifest(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    async fn get_notes(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        Ok(String::new())
+    }
+
+    async fn get_hooks(
+        &self,
+        _namespace: &non_blank_string_rs::NonBlankString,
+        _release_name: &non_blank_string_rs::NonBlankString,
+        _revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    async fn get_metadata(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        Ok(HelmReleaseMetadata {
+            name: release_name.to_string(),
+            namespace: namespace.to_string(),
+            revision: revision.unwrap_or(1),
+            status: self.1.clone(),
+            ..HelmReleaseMetadata::default()
+        })
+    }
+
+    async fn template(
+        &self,
+        _request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    async fn lint(
+        &self,
+        chart_path: &std::path::Path,
+        _values: &[ValuesSource],
+        _strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        Ok(LintReport {
+            charts: vec![ChartLintResult {
+                chart: chart_path.display().to_string(),
+                findings: vec![],
+            }],
+            linted: 1,
+            failed: 0,
+        })
+    }
+
+    async fn repo_add(
+        &self,
+        _name: &non_blank_string_rs::NonBlankString,
+        _url: &non_blank_string_rs::NonBlankString,
+        _options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn repo_update(
+        &self,
+        _names: &[non_blank_string_rs::NonBlankString],
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    async fn repo_remove(
+        &self,
+        _name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn search_repo(
+        &self,
+        _keyword: &non_blank_string_rs::NonBlankString,
+        _versions: bool,
+        _devel: bool,
+        _version_constraint: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        Ok(vec![])
+    }
+
+    async fn registry_login(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+        _credentials: &RepoCredentials,
+        _options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn registry_logout(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn push(
+        &self,
+        _chart_package: &std::path::Path,
+        _remote: &non_blank_string_rs::NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        Ok(PushedChart::default())
+    }
+
+    async fn pull(
+        &self,
+        _chart: &non_blank_string_rs::NonBlankString,
+        _chart_version: Option<&non_blank_string_rs::NonBlankString>,
+        _destination: &std::path::Path,
     ) -> Result<(), HelmWrapperError> {
         Ok(())
     }
 }
+
+impl HelmExecutor for SimulatorHelmExecutor {
+    async fn list(
+        &self,
+        namespace: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<HelmListItem>, HelmWrapperError> {
+        Ok(self.state().list(namespace))
+    }
+
+    async fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        self.state().install_or_upgrade(request)
+    }
+
+    async fn uninstall(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        self.state().uninstall(namespace, release_name)
+    }
+
+    async fn rollback(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        _options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        self.state().rollback(namespace, release_name, revision)
+    }
+
+    async fn history(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        self.state().history(namespace, release_name, max)
+    }
+
+    async fn status(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        self.state().status(namespace, release_name, revision)
+    }
+
+    async fn get_values(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        _all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        self.state().get_values(namespace, release_name, revision)
+    }
+
+    async fn get_manifest(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        self.state()
+            .ensure_revision(namespace, release_name, revision)
+            .map(|_| vec![])
+    }
+
+    async fn get_notes(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        self.state()
+            .ensure_revision(namespace, release_name, revision)
+            .map(|_| String::new())
+    }
+
+    async fn get_hooks(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        self.state()
+            .ensure_revision(namespace, release_name, revision)
+            .map(|_| vec![])
+    }
+
+    async fn get_metadata(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        self.state().get_metadata(namespace, release_name, revision)
+    }
+
+    async fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        request.validate()?;
+        Ok(vec![])
+    }
+
+    async fn lint(
+        &self,
+        chart_path: &std::path::Path,
+        _values: &[ValuesSource],
+        _strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        Ok(LintReport {
+            charts: vec![ChartLintResult {
+                chart: chart_path.display().to_string(),
+                findings: vec![],
+            }],
+            linted: 1,
+            failed: 0,
+        })
+    }
+
+    async fn repo_add(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+        url: &non_blank_string_rs::NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        self.state().repo_add(name, url, options)
+    }
+
+    async fn repo_update(
+        &self,
+        names: &[non_blank_string_rs::NonBlankString],
+    ) -> Result<(), HelmWrapperError> {
+        self.state().repo_update(names)
+    }
+
+    async fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        Ok(self.state().repo_list())
+    }
+
+    async fn repo_remove(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        self.state().repo_remove(name)
+    }
+
+    async fn search_repo(
+        &self,
+        keyword: &non_blank_string_rs::NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        self.state()
+            .search_repo(keyword, versions, devel, version_constraint)
+    }
+
+    async fn registry_login(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+        _credentials: &RepoCredentials,
+        _options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn registry_logout(
+        &self,
+        _host: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+
+    async fn push(
+        &self,
+        _chart_package: &std::path::Path,
+        _remote: &non_blank_string_rs::NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        Ok(PushedChart::default())
+    }
+
+    async fn pull(
+        &self,
+        _chart: &non_blank_string_rs::NonBlankString,
+        _chart_version: Option<&non_blank_string_rs::NonBlankString>,
+        _destination: &std::path::Path,
+    ) -> Result<(), HelmWrapperError> {
+        Ok(())
+    }
+}
+
+impl HelmExecutor for MockHelmExecutor {
+    async fn list(
+        &self,
+        namespace: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<HelmListItem>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::List {
+                namespace: namespace.map(|namespace| namespace.to_string()),
+            },
+        )
+        .await?;
+
+        self.simulator().list(namespace).await
+    }
+
+    async fn install_or_upgrade_with(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        let status = intercept(self, HelmCall::InstallOrUpgrade(request.clone())).await?;
+
+        let mut release = self.simulator().install_or_upgrade_with(request).await?;
+
+        if let Some(status) = status {
+            if request.dry_run.is_none() {
+                self.simulator().set_status(
+                    &request.namespace,
+                    &request.release_name,
+                    status.clone(),
+                )?;
+            }
+
+            release.info.status = status;
+        }
+
+        Ok(release)
+    }
+
+    async fn uninstall(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Uninstall {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+            },
+        )
+        .await?;
+
+        self.simulator().uninstall(namespace, release_name).await
+    }
+
+    async fn rollback(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        options: &RollbackOptions,
+    ) -> Result<u32, HelmWrapperError> {
+        let status = intercept(
+            self,
+            HelmCall::Rollback {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+                options: options.clone(),
+            },
+        )
+        .await?;
+
+        let revision = self
+            .simulator()
+            .rollback(namespace, release_name, revision, options)
+            .await?;
+
+        if let Some(status) = status {
+            self.simulator()
+                .set_status(namespace, release_name, status)?;
+        }
+
+        Ok(revision)
+    }
+
+    async fn history(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::History {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                max,
+            },
+        )
+        .await?;
+
+        self.simulator().history(namespace, release_name, max).await
+    }
+
+    async fn status(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        let status = intercept(
+            self,
+            HelmCall::Status {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )
+        .await?;
+
+        let mut release = self
+            .simulator()
+            .status(namespace, release_name, revision)
+            .await?;
+
+        if let Some(status) = status {
+            release.info.status = status;
+        }
+
+        Ok(release)
+    }
+
+    async fn get_values(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+        all: bool,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetValues {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+                all,
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .get_values(namespace, release_name, revision, all)
+            .await
+    }
+
+    async fn get_manifest(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetManifest {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .get_manifest(namespace, release_name, revision)
+            .await
+    }
+
+    async fn get_notes(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<String, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetNotes {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .get_notes(namespace, release_name, revision)
+            .await
+    }
+
+    async fn get_hooks(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::GetHooks {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .get_hooks(namespace, release_name, revision)
+            .await
+    }
+
+    async fn get_metadata(
+        &self,
+        namespace: &non_blank_string_rs::NonBlankString,
+        release_name: &non_blank_string_rs::NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        let status = intercept(
+            self,
+            HelmCall::GetMetadata {
+                namespace: namespace.to_string(),
+                release_name: release_name.to_string(),
+                revision,
+            },
+        )
+        .await?;
+
+        let mut metadata = self
+            .simulator()
+            .get_metadata(namespace, release_name, revision)
+            .await?;
+
+        if let Some(status) = status {
+            metadata.status = status;
+        }
+
+        Ok(metadata)
+    }
+
+    async fn template(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<Vec<ManifestDocument>, HelmWrapperError> {
+        intercept(self, HelmCall::Template(request.clone())).await?;
+
+        self.simulator().template(request).await
+    }
+
+    async fn lint(
+        &self,
+        chart_path: &std::path::Path,
+        values: &[ValuesSource],
+        strict: bool,
+    ) -> Result<LintReport, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Lint {
+                chart_path: chart_path.to_path_buf(),
+                values: values.to_vec(),
+                strict,
+            },
+        )
+        .await?;
+
+        self.simulator().lint(chart_path, values, strict).await
+    }
+
+    async fn repo_add(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+        url: &non_blank_string_rs::NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(self, HelmCall::repo_add(name, url, options)).await?;
+
+        self.simulator().repo_add(name, url, options).await
+    }
+
+    async fn repo_update(
+        &self,
+        names: &[non_blank_string_rs::NonBlankString],
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RepoUpdate {
+                names: names.iter().map(|name| name.to_string()).collect(),
+            },
+        )
+        .await?;
+
+        self.simulator().repo_update(names).await
+    }
+
+    async fn repo_list(&self) -> Result<Vec<HelmRepository>, HelmWrapperError> {
+        intercept(self, HelmCall::RepoList).await?;
+
+        self.simulator().repo_list().await
+    }
+
+    async fn repo_remove(
+        &self,
+        name: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RepoRemove {
+                name: name.to_string(),
+            },
+        )
+        .await?;
+
+        self.simulator().repo_remove(name).await
+    }
+
+    async fn search_repo(
+        &self,
+        keyword: &non_blank_string_rs::NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&non_blank_string_rs::NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::SearchRepo {
+                keyword: keyword.to_string(),
+                versions,
+                devel,
+                version_constraint: version_constraint.map(|constraint| constraint.to_string()),
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .search_repo(keyword, versions, devel, version_constraint)
+            .await
+    }
+
+    async fn registry_login(
+        &self,
+        host: &non_blank_string_rs::NonBlankString,
+        credentials: &RepoCredentials,
+        options: &RegistryLoginOptions,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RegistryLogin {
+                host: host.to_string(),
+                credentials: credentials.into(),
+                options: options.clone(),
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .registry_login(host, credentials, options)
+            .await
+    }
+
+    async fn registry_logout(
+        &self,
+        host: &non_blank_string_rs::NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::RegistryLogout {
+                host: host.to_string(),
+            },
+        )
+        .await?;
+
+        self.simulator().registry_logout(host).await
+    }
+
+    async fn push(
+        &self,
+        chart_package: &std::path::Path,
+        remote: &non_blank_string_rs::NonBlankString,
+    ) -> Result<PushedChart, HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Push {
+                chart_package: chart_package.to_path_buf(),
+                remote: remote.to_string(),
+            },
+        )
+        .await?;
+
+        self.simulator().push(chart_package, remote).await
+    }
+
+    async fn pull(
+        &self,
+        chart: &non_blank_string_rs::NonBlankString,
+        chart_version: Option<&non_blank_string_rs::NonBlankString>,
+        destination: &std::path::Path,
+    ) -> Result<(), HelmWrapperError> {
+        intercept(
+            self,
+            HelmCall::Pull {
+                chart: chart.to_string(),
+                chart_version: chart_version.map(|version| version.to_string()),
+                destination: destination.to_path_buf(),
+            },
+        )
+        .await?;
+
+        self.simulator()
+            .pull(chart, chart_version, destination)
+            .await
+    }
+}
+
+/// Record call of mock, wait for programmed delay and return programmed error or status
+async fn intercept(
+    executor: &MockHelmExecutor,
+    call: HelmCall,
+) -> Result<Option<HelmDeployStatus>, HelmWrapperError> {
+    let outcome = executor.intercept(call);
+
+    if let Some(delay) = outcome.delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    match outcome.error {
+        Some(error) => Err(error),
+        None => Ok(outcome.status),
+    }
+}
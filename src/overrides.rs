@@ -0,0 +1,275 @@
+use std::{fmt, path::PathBuf};
+
+use crate::request::{ValueOverride, ValueOverrideKind};
+
+/// Segment of chart value path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Map key, taken literally: dots, commas, brackets etc. are escaped
+    Key(String),
+    /// List index, `[N]`
+    Index(usize),
+}
+
+/// Path to chart value, rendered in helm strvals syntax with escaping.
+///
+/// ```
+/// use helm_wrapper_rs::overrides::ValuePath;
+///
+/// let path = ValuePath::new("ingress")
+///     .key("annotations")
+///     .key("kubernetes.io/ingress.class");
+///
+/// assert_eq!(path.to_string(), r"ingress.annotations.kubernetes\.io/ingress\.class");
+///
+/// let path = ValuePath::new("env").index(0).key("name");
+///
+/// assert_eq!(path.to_string(), "env[0].name");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValuePath(Vec<PathSegment>);
+
+impl ValuePath {
+    /// Path starts with top-level key, helm doesn't support top-level lists
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(vec![PathSegment::Key(key.into())])
+    }
+
+    /// Nested map key
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.0.push(PathSegment::Key(key.into()));
+        self
+    }
+
+    /// List index
+    pub fn index(mut self, index: usize) -> Self {
+        self.0.push(PathSegment::Index(index));
+        self
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ValuePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    f.write_str(&escape_key(key))?;
+                }
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Typed chart value override, selects helm flag and escaping
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverrideValue {
+    /// `--set`, helm infers value type: `true`, `1`, `null` are not strings
+    Inferred(String),
+    /// `--set-string`, value is always a string
+    String(String),
+    /// `--set-json`, value is passed as JSON as is
+    Json(serde_json::Value),
+    /// `--set-file`, value is read by helm from the file
+    File(PathBuf),
+}
+
+impl OverrideValue {
+    /// Build override for `path` with escaped key and value
+    pub fn to_override(&self, path: &ValuePath) -> ValueOverride {
+        let (kind, value) = match self {
+            OverrideValue::Inferred(value) => (ValueOverrideKind::Set, escape_value(value)),
+            OverrideValue::String(value) => (ValueOverrideKind::SetString, escape_value(value)),
+            OverrideValue::Json(value) => (ValueOverrideKind::SetJson, value.to_string()),
+            OverrideValue::File(path) => (
+                ValueOverrideKind::SetFile,
+                escape_value(&path.to_string_lossy()),
+            ),
+        };
+
+        ValueOverride {
+            kind,
+            key: path.to_string(),
+            value,
+        }
+    }
+}
+
+impl From<&str> for OverrideValue {
+    fn from(value: &str) -> Self {
+        OverrideValue::String(value.to_string())
+    }
+}
+
+impl From<String> for OverrideValue {
+    fn from(value: String) -> Self {
+        OverrideValue::String(value)
+    }
+}
+
+impl From<serde_json::Value> for OverrideValue {
+    fn from(value: serde_json::Value) -> Self {
+        OverrideValue::Json(value)
+    }
+}
+
+/// Escape single key for helm strvals parser. Key ends on `=`, `[`, `,` and `.`,
+/// backslash makes the next character literal.
+pub fn escape_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+
+    for c in key.chars() {
+        if matches!(c, '\\' | '=' | '[' | ']' | ',' | '.') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Escape value for helm strvals parser. Value ends on `,`, leading `{` starts a list,
+/// backslash makes the next character literal.
+pub fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for (i, c) in value.chars().enumerate() {
+        if matches!(c, '\\' | ',') || (i == 0 && c == '{') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod overrides_tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use crate::{
+        overrides::{escape_key, escape_value, OverrideValue, PathSegment, ValuePath},
+        request::{ValueOverride, ValueOverrideKind},
+    };
+
+    #[test]
+    fn plain_key_is_not_escaped() {
+        assert_eq!(escape_key("replicaCount"), "replicaCount");
+        assert_eq!(escape_key("app-name_1"), "app-name_1");
+        assert_eq!(escape_key("kubernetes/ingress"), "kubernetes/ingress");
+    }
+
+    #[test]
+    fn key_separators_are_escaped() {
+        assert_eq!(escape_key("a.b"), r"a\.b");
+        assert_eq!(escape_key("a,b"), r"a\,b");
+        assert_eq!(escape_key("a=b"), r"a\=b");
+        assert_eq!(escape_key("a[0]"), r"a\[0\]");
+        assert_eq!(escape_key(r"a\b"), r"a\\b");
+        assert_eq!(escape_key(".."), r"\.\.");
+    }
+
+    #[test]
+    fn key_whitespace_and_unicode_are_kept() {
+        assert_eq!(escape_key("a b"), "a b");
+        assert_eq!(escape_key("ключ.é"), r"ключ\.é");
+    }
+
+    #[test]
+    fn plain_value_is_not_escaped() {
+        assert_eq!(escape_value("nginx"), "nginx");
+        assert_eq!(escape_value("a.b=c[0]"), "a.b=c[0]");
+        assert_eq!(escape_value(""), "");
+        assert_eq!(escape_value("a}"), "a}");
+    }
+
+    #[test]
+    fn value_separators_are_escaped() {
+        assert_eq!(escape_value("a,b"), r"a\,b");
+        assert_eq!(escape_value(r"C:\temp"), r"C:\\temp");
+        assert_eq!(escape_value(",,"), r"\,\,");
+    }
+
+    #[test]
+    fn only_leading_brace_of_value_is_escaped() {
+        assert_eq!(escape_value("{a,b}"), r"\{a\,b}");
+        assert_eq!(escape_value("a{b}"), "a{b}");
+    }
+
+    #[test]
+    fn path_with_keys_and_indices() {
+        let path = ValuePath::new("a").key("b.c").index(0).index(1).key("d");
+
+        assert_eq!(path.to_string(), r"a.b\.c[0][1].d");
+        assert_eq!(
+            path.segments(),
+            &[
+                PathSegment::Key("a".to_string()),
+                PathSegment::Key("b.c".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Index(1),
+                PathSegment::Key("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bracket_in_key_is_not_index() {
+        assert_eq!(ValuePath::new("a[0]").to_string(), r"a\[0\]");
+        assert_eq!(ValuePath::new("a").index(0).to_string(), "a[0]");
+    }
+
+    #[test]
+    fn inferred_value_uses_set() {
+        let value_override =
+            OverrideValue::Inferred("1,2".to_string()).to_override(&ValuePath::new("a.b"));
+
+        assert_eq!(
+            value_override,
+            ValueOverride {
+                kind: ValueOverrideKind::Set,
+                key: r"a\.b".to_string(),
+                value: r"1\,2".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn string_value_uses_set_string() {
+        let value_override = OverrideValue::from("true").to_override(&ValuePath::new("flag"));
+
+        assert_eq!(value_override.kind, ValueOverrideKind::SetString);
+        assert_eq!(value_override.value, "true");
+    }
+
+    #[test]
+    fn json_value_is_not_escaped() {
+        let value_override = OverrideValue::from(json!({"a": [1, 2], "b": "x,y"}))
+            .to_override(&ValuePath::new("cfg").index(0));
+
+        assert_eq!(value_override.kind, ValueOverrideKind::SetJson);
+        assert_eq!(value_override.key, "cfg[0]");
+        assert_eq!(value_override.value, r#"{"a":[1,2],"b":"x,y"}"#);
+    }
+
+    #[test]
+    fn file_path_is_escaped() {
+        let value_override = OverrideValue::File(PathBuf::from("certs/a,b.pem"))
+            .to_override(&ValuePath::new("tls").key("crt"));
+
+        assert_eq!(value_override.kind, ValueOverrideKind::SetFile);
+        assert_eq!(value_override.key, "tls.crt");
+        assert_eq!(value_override.value, r"certs/a\,b.pem");
+    }
+}
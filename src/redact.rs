@@ -0,0 +1,541 @@
+use std::fmt;
+
+/// Replacement for sensitive values
+pub const REDACTED: &str = "***";
+
+/// Scrubs sensitive values from logs and error payloads.
+///
+/// Sensitive keys are case-insensitive glob patterns (`*` - any chars, `?` - single char),
+/// matched against the last key of value path (`db.password` -> `password`) and the path
+/// with any leading keys dropped, so `tls.key` also matches `config.tls.key` in helm JSON.
+/// Secrets are literal values masked wherever they appear.
+///
+/// ```
+/// use helm_wrapper_rs::redact::Redactor;
+///
+/// let redactor = Redactor::new()
+///     .sensitive_key("*password*")
+///     .sensitive_key("auth.token")
+///     .secret("s3cr3t");
+///
+/// assert_eq!(redactor.redact_text("db.password=qwerty"), "db.password=***");
+/// assert_eq!(redactor.redact_text("auth.token=abc"), "auth.token=***");
+/// assert_eq!(redactor.redact_text("token is s3cr3t"), "token is ***");
+/// ```
+#[derive(Clone, Default, PartialEq)]
+pub struct Redactor {
+    sensitive_keys: Vec<String>,
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    /// Redactor without any sensitive keys, logs are kept as is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add sensitive key or glob pattern, e.g. `password`, `*token*`, `db.*`
+    pub fn sensitive_key(mut self, pattern: impl Into<String>) -> Self {
+        self.sensitive_keys.push(pattern.into().to_lowercase());
+        self
+    }
+
+    /// Add literal secret value, masked in any text
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        let secret = secret.into();
+
+        if !secret.is_empty() {
+            self.secrets.push(secret);
+        }
+
+        self
+    }
+
+    /// Check if value with key (or path) should be masked
+    pub fn is_sensitive_key(&self, key: &str) -> bool {
+        if self.sensitive_keys.is_empty() {
+            return false;
+        }
+
+        let key = key.trim_start_matches('-').replace('\\', "").to_lowercase();
+
+        let last_key = key.rsplit('.').next().unwrap_or_default();
+        let last_key = last_key
+            .split_once('[')
+            .map(|(last_key, _index)| last_key)
+            .unwrap_or(last_key);
+
+        // path suffixes aligned to dots: `config.tls.key`, `tls.key`, `key`
+        let suffixes: Vec<&str> = std::iter::once(key.as_str())
+            .chain(key.match_indices('.').map(|(i, _)| &key[i + 1..]))
+            .collect();
+
+        self.sensitive_keys.iter().any(|pattern| {
+            glob_match(pattern, last_key)
+                || suffixes.iter().any(|suffix| glob_match(pattern, suffix))
+        })
+    }
+
+    /// Value of `key` for log: masked if key is sensitive, otherwise scrubbed as text
+    pub fn redact_value(&self, key: &str, value: &str) -> String {
+        if self.is_sensitive_key(key) {
+            REDACTED.to_string()
+        } else {
+            self.redact_text(value)
+        }
+    }
+
+    /// Scrub free text: helm output, error message, command line.
+    ///
+    /// JSON documents are scrubbed by keys, other text line by line: `key: value` YAML
+    /// entries (with nested blocks), `key=value` pairs and `"key": value` JSON fragments.
+    pub fn redact_text(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+
+        let trimmed = text.trim_start();
+
+        if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(text) {
+                return self.redact_json(&value).to_string();
+            }
+        }
+
+        let mut lines = vec![];
+        // YAML keys of enclosing entries with their indentation
+        let mut parents: Vec<(usize, String)> = vec![];
+        // indentation of sensitive YAML entry, whose nested lines are masked
+        let mut sensitive_block: Option<usize> = None;
+
+        for line in text.split('\n') {
+            let trimmed = line.trim_start();
+            let indent = line.len() - trimmed.len();
+
+            if let Some(block_indent) = sensitive_block {
+                if trimmed.is_empty() {
+                    lines.push(line.to_string());
+                    continue;
+                }
+
+                if indent > block_indent {
+                    lines.push(format!("{}{REDACTED}", &line[..indent]));
+                    continue;
+                }
+
+                sensitive_block = None;
+            }
+
+            let Some((key, key_indent, value_start)) = yaml_entry(line) else {
+                lines.push(self.redact_pairs(line));
+                continue;
+            };
+
+            while parents
+                .last()
+                .is_some_and(|(parent_indent, _)| *parent_indent >= key_indent)
+            {
+                parents.pop();
+            }
+
+            let path = parents
+                .iter()
+                .map(|(_, parent)| parent.as_str())
+                .chain([key])
+                .collect::<Vec<&str>>()
+                .join(".");
+
+            parents.push((key_indent, key.to_string()));
+
+            if !self.is_sensitive_key(&path) {
+                lines.push(self.redact_pairs(line));
+                continue;
+            }
+
+            let value = line[value_start..].trim();
+
+            if value.is_empty() || value.starts_with('|') || value.starts_with('>') {
+                sensitive_block = Some(key_indent);
+                lines.push(line.to_string());
+            } else {
+                lines.push(format!("{}{REDACTED}", &line[..value_start]));
+            }
+        }
+
+        self.redact_secrets(&lines.join("\n"))
+    }
+
+    /// Scrub JSON document, values of sensitive keys are replaced with `***`
+    pub fn redact_json(&self, value: &serde_json::Value) -> serde_json::Value {
+        self.redact_json_at("", value)
+    }
+
+    fn redact_json_at(&self, path: &str, value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(key, value)| {
+                        let path = if path.is_empty() {
+                            key.to_string()
+                        } else {
+                            format!("{path}.{key}")
+                        };
+
+                        let value = if self.is_sensitive_key(&path) {
+                            serde_json::Value::String(REDACTED.to_string())
+                        } else {
+                            self.redact_json_at(&path, value)
+                        };
+
+                        (key.to_string(), value)
+                    })
+                    .collect(),
+            ),
+            serde_json::Value::Array(items) => serde_json::Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.redact_json_at(path, item))
+                    .collect(),
+            ),
+            serde_json::Value::String(text) => serde_json::Value::String(self.redact_secrets(text)),
+            other => other.clone(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sensitive_keys.is_empty() && self.secrets.is_empty()
+    }
+
+    fn redact_secrets(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+
+    /// Mask values in `key=value`, `key: value` and `"key": value` fragments of single line
+    fn redact_pairs(&self, line: &str) -> String {
+        if self.sensitive_keys.is_empty() {
+            return line.to_string();
+        }
+
+        let mut redacted = String::with_capacity(line.len());
+        let mut key_start = 0;
+        let mut position = 0;
+
+        while let Some(c) = line[position..].chars().next() {
+            let next = position + c.len_utf8();
+
+            if c == '"' {
+                // quoted key, e.g. JSON fragment
+                let end = quoted_end(line, position);
+                redacted.push_str(&line[position..end]);
+
+                let quoted = &line[position + 1..end];
+                let key = quoted.strip_suffix('"').unwrap_or(quoted);
+                let separator = skip_whitespace(line, end);
+
+                if line[separator..].starts_with(':') && self.is_sensitive_key(key) {
+                    let value_start = skip_whitespace(line, separator + 1);
+                    redacted.push_str(&line[end..value_start]);
+                    redacted.push_str(REDACTED);
+                    position = value_end(line, value_start, false);
+                } else {
+                    position = end;
+                }
+
+                key_start = position;
+                continue;
+            }
+
+            let is_pair =
+                c == '=' || (c == ':' && line[next..].starts_with([' ', '\t', '"', '\'']));
+
+            if is_pair {
+                let key = &line[key_start..position];
+
+                if !key.is_empty() && self.is_sensitive_key(key) {
+                    let value_start = skip_whitespace(line, next);
+                    redacted.push_str(&line[position..value_start]);
+                    redacted.push_str(REDACTED);
+                    position = value_end(line, value_start, c == '=');
+                    key_start = position;
+                    continue;
+                }
+            }
+
+            redacted.push(c);
+            position = next;
+
+            if !is_key_char(c) {
+                key_start = position;
+            }
+        }
+
+        redacted
+    }
+}
+
+/// Secrets are never printed
+impl fmt::Debug for Redactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redactor")
+            .field("sensitive_keys", &self.sensitive_keys)
+            .field("secrets", &self.secrets.len())
+            .finish()
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || "-_./[]\\".contains(c)
+}
+
+/// YAML mapping entry `key: value` or `- key: value`, returns key, its indentation
+/// and value position
+fn yaml_entry(line: &str) -> Option<(&str, usize, usize)> {
+    let trimmed = line.trim_start();
+    let entry = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+    let key_indent = line.len() - entry.len();
+    let (key, value) = entry.split_once(':')?;
+
+    if !(value.is_empty() || value.starts_with(' ')) {
+        return None;
+    }
+
+    let key = key
+        .strip_prefix('"')
+        .and_then(|key| key.strip_suffix('"'))
+        .unwrap_or(key);
+
+    if key.is_empty() || !key.chars().all(is_key_char) {
+        return None;
+    }
+
+    let value_start = line.len() - value.len();
+    let value_start = value_start + (value.len() - value.trim_start().len());
+
+    Some((key, key_indent, value_start))
+}
+
+/// Position of first non-whitespace char from `start`
+fn skip_whitespace(line: &str, start: usize) -> usize {
+    line.len() - line[start..].trim_start().len()
+}
+
+/// Position after closing quote of string starting at `start`
+fn quoted_end(line: &str, start: usize) -> usize {
+    let quote = line[start..].chars().next().unwrap_or('"');
+    let mut escaped = false;
+
+    for (offset, c) in line[start + 1..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return start + 1 + offset + 1;
+        }
+    }
+
+    line.len()
+}
+
+/// Position after value starting at `start`: quoted string, or text until whitespace or
+/// unescaped comma (and closing bracket for non-assignment values)
+fn value_end(line: &str, start: usize, assignment: bool) -> usize {
+    if line[start..].starts_with(['"', '\'']) {
+        return quoted_end(line, start);
+    }
+
+    let mut escaped = false;
+
+    for (offset, c) in line[start..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c.is_whitespace() || c == ',' || (!assignment && (c == '}' || c == ']')) {
+            return start + offset;
+        }
+    }
+
+    line.len()
+}
+
+/// Glob match with `*` and `?`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // last `*` position in pattern and text position it was matched at
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod redact_tests {
+    use serde_json::json;
+
+    use crate::redact::{glob_match, Redactor};
+
+    fn redactor() -> Redactor {
+        Redactor::new()
+            .sensitive_key("*password*")
+            .sensitive_key("token")
+            .sensitive_key("tls.key")
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("password", "password"));
+        assert!(glob_match("*password*", "dbpassword1"));
+        assert!(glob_match("db.*", "db.user"));
+        assert!(glob_match("to?en", "token"));
+        assert!(!glob_match("password", "password2"));
+        assert!(!glob_match("db.*", "redis.user"));
+    }
+
+    #[test]
+    fn sensitive_keys_match_last_key_or_path() {
+        let redactor = redactor();
+
+        assert!(redactor.is_sensitive_key("db.password"));
+        assert!(redactor.is_sensitive_key("DB.adminPassword"));
+        assert!(redactor.is_sensitive_key("auth.token"));
+        assert!(redactor.is_sensitive_key("tokens[0].token"));
+        assert!(redactor.is_sensitive_key("--password"));
+        assert!(redactor.is_sensitive_key("tls.key"));
+        assert!(redactor.is_sensitive_key("config.tls.key"));
+        assert!(redactor.is_sensitive_key("chart.values.tls.key"));
+        assert!(!redactor.is_sensitive_key("mtls.key"));
+        assert!(!redactor.is_sensitive_key("tls.key.path"));
+        assert!(!redactor.is_sensitive_key("key"));
+        assert!(!redactor.is_sensitive_key("token.name"));
+        assert!(!Redactor::new().is_sensitive_key("password"));
+    }
+
+    #[test]
+    fn redact_set_arguments() {
+        let redactor = redactor();
+
+        assert_eq!(
+            redactor.redact_text("db.password=qwerty"),
+            "db.password=***"
+        );
+        assert_eq!(
+            redactor.redact_text("a=1,db.password=qw\\,erty,b=2"),
+            "a=1,db.password=***,b=2"
+        );
+        assert_eq!(
+            redactor.redact_text("--set db.password=x --set replicaCount=2 --password=y"),
+            "--set db.password=*** --set replicaCount=2 --password=***"
+        );
+        assert_eq!(redactor.redact_text("image.tag=1.0"), "image.tag=1.0");
+    }
+
+    #[test]
+    fn redact_yaml() {
+        let yaml = r#"db:
+  user: admin
+  password: "qwerty 123"
+tls:
+  key: |
+    -----BEGIN KEY-----
+    abc
+  crt: plain
+- token: abc
+"#;
+
+        assert_eq!(
+            redactor().redact_text(yaml),
+            r#"db:
+  user: admin
+  password: ***
+tls:
+  key: |
+    ***
+    ***
+  crt: plain
+- token: ***
+"#
+        );
+    }
+
+    #[test]
+    fn redact_json_document() {
+        let redacted = redactor().redact_text(
+            r#"{"name":"app","config":{"db":{"password":"qwerty","port":5432},"tls":{"key":"k"}}}"#,
+        );
+
+        let redacted: serde_json::Value = serde_json::from_str(&redacted).unwrap();
+
+        assert_eq!(
+            redacted,
+            json!({"name": "app", "config": {"db": {"password": "***", "port": 5432}, "tls": {"key": "***"}}})
+        );
+
+        assert_eq!(
+            redactor().redact_json(&json!({"tls": {"key": "k"}, "items": [{"token": 1}]})),
+            json!({"tls": {"key": "***"}, "items": [{"token": "***"}]})
+        );
+    }
+
+    #[test]
+    fn redact_json_and_debug_fragments() {
+        let redactor = redactor();
+
+        assert_eq!(
+            redactor.redact_text(r#"Error: invalid {"password": "a b", "user": "x"}"#),
+            r#"Error: invalid {"password": ***, "user": "x"}"#
+        );
+        assert_eq!(
+            redactor.redact_text(r#"Item { name: "x", password: "y z" }"#),
+            r#"Item { name: "x", password: *** }"#
+        );
+        assert_eq!(
+            redactor.redact_text("url https://host:443/path"),
+            "url https://host:443/path"
+        );
+    }
+
+    #[test]
+    fn redact_literal_secrets() {
+        let redactor = Redactor::new().secret("s3cr3t").secret("");
+
+        assert_eq!(
+            redactor.redact_text("Error: login failed for s3cr3t"),
+            "Error: login failed for ***"
+        );
+        assert_eq!(
+            redactor.redact_json(&json!({"a": ["xs3cr3tx"]})),
+            json!({"a": ["x***x"]})
+        );
+    }
+
+    #[test]
+    fn debug_hides_secrets() {
+        let redactor = Redactor::new().sensitive_key("password").secret("s3cr3t");
+
+        let debug = format!("{redactor:?}");
+
+        assert!(debug.contains("password"));
+        assert!(!debug.contains("s3cr3t"));
+    }
+}
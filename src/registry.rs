@@ -0,0 +1,250 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+/// Chart reference as accepted by helm install, template and pull
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChartReference {
+    /// Chart from added repository: `repo/chart`
+    Repository { repo: String, chart: String },
+    /// Chart in OCI registry: `oci://registry[:port]/path/chart[:tag]`
+    Oci {
+        registry: String,
+        /// Path in registry including chart name, e.g. `charts/whoami`
+        path: String,
+        tag: Option<String>,
+    },
+    /// Chart archive URL: `https://example.com/whoami-5.2.0.tgz`
+    Url(String),
+    /// Local chart directory or archive
+    Path(PathBuf),
+}
+
+impl ChartReference {
+    /// Parse chart reference. `name/chart` with single slash is a repository chart,
+    /// other references without scheme are local paths.
+    pub fn parse(reference: &str) -> Self {
+        if let Some(rest) = reference.strip_prefix("oci://") {
+            let (registry, path) = rest.split_once('/').unwrap_or((rest, ""));
+
+            // tag is in the last path segment only, registry may have port
+            let (path, tag) = match path.rsplit_once('/') {
+                Some((parent, last)) => match last.split_once(':') {
+                    Some((chart, tag)) if !last.contains('@') => {
+                        (format!("{parent}/{chart}"), Some(tag.to_string()))
+                    }
+                    _ => (path.to_string(), None),
+                },
+                None => match path.split_once(':') {
+                    Some((chart, tag)) if !path.contains('@') => {
+                        (chart.to_string(), Some(tag.to_string()))
+                    }
+                    _ => (path.to_string(), None),
+                },
+            };
+
+            return ChartReference::Oci {
+                registry: registry.to_string(),
+                path,
+                tag,
+            };
+        }
+
+        if reference.starts_with("https://") || reference.starts_with("http://") {
+            return ChartReference::Url(reference.to_string());
+        }
+
+        let is_path = reference.starts_with(['/', '.', '~'])
+            || reference.ends_with(".tgz")
+            || reference.matches('/').count() != 1;
+
+        match reference.split_once('/') {
+            Some((repo, chart)) if !is_path && !repo.is_empty() && !chart.is_empty() => {
+                ChartReference::Repository {
+                    repo: repo.to_string(),
+                    chart: chart.to_string(),
+                }
+            }
+            _ => ChartReference::Path(PathBuf::from(reference)),
+        }
+    }
+
+    pub fn is_oci(&self) -> bool {
+        matches!(self, ChartReference::Oci { .. })
+    }
+
+    /// Registry host of OCI chart, e.g. `ghcr.io`
+    pub fn registry(&self) -> Option<&str> {
+        match self {
+            ChartReference::Oci { registry, .. } => Some(registry),
+            _ => None,
+        }
+    }
+
+    /// OCI tag from reference, e.g. `5.2.0` for `oci://ghcr.io/charts/whoami:5.2.0`
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            ChartReference::Oci { tag, .. } => tag.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The same reference without OCI tag, helm expects chart version in `--version`
+    pub fn without_tag(&self) -> Self {
+        match self {
+            ChartReference::Oci { registry, path, .. } => ChartReference::Oci {
+                registry: registry.clone(),
+                path: path.clone(),
+                tag: None,
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+impl FromStr for ChartReference {
+    type Err = std::convert::Infallible;
+
+    fn from_str(reference: &str) -> Result<Self, Self::Err> {
+        Ok(ChartReference::parse(reference))
+    }
+}
+
+impl fmt::Display for ChartReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChartReference::Repository { repo, chart } => write!(f, "{repo}/{chart}"),
+            ChartReference::Oci {
+                registry,
+                path,
+                tag,
+            } => {
+                write!(f, "oci://{registry}/{path}")?;
+
+                if let Some(tag) = tag {
+                    write!(f, ":{tag}")?;
+                }
+
+                Ok(())
+            }
+            ChartReference::Url(url) => f.write_str(url),
+            ChartReference::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Options for `helm registry login`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryLoginOptions {
+    /// Allow connections to TLS registry without certs (`--insecure`)
+    pub insecure: bool,
+    /// Verify certificates of HTTPS-enabled servers using this CA bundle (`--ca-file`)
+    pub ca_file: Option<PathBuf>,
+    /// Identify registry client using this SSL certificate file (`--cert-file`)
+    pub cert_file: Option<PathBuf>,
+    /// Identify registry client using this SSL key file (`--key-file`)
+    pub key_file: Option<PathBuf>,
+}
+
+/// Result of `helm push`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushedChart {
+    /// Pushed reference, e.g. `ghcr.io/charts/whoami:5.2.0`
+    pub reference: Option<String>,
+    /// Manifest digest, e.g. `sha256:...`
+    pub digest: Option<String>,
+}
+
+/// Parse `Pushed: ...` and `Digest: ...` lines of `helm push` output
+pub fn parse_pushed_chart(output: &str) -> PushedChart {
+    let mut pushed = PushedChart::default();
+
+    for line in output.lines() {
+        if let Some(reference) = line.trim().strip_prefix("Pushed:") {
+            pushed.reference = Some(reference.trim().to_string());
+        } else if let Some(digest) = line.trim().strip_prefix("Digest:") {
+            pushed.digest = Some(digest.trim().to_string());
+        }
+    }
+
+    pushed
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use std::path::PathBuf;
+
+    use crate::registry::{parse_pushed_chart, ChartReference, PushedChart};
+
+    #[test]
+    fn parse_oci_references() {
+        assert_eq!(
+            ChartReference::parse("oci://ghcr.io/charts/whoami:5.2.0"),
+            ChartReference::Oci {
+                registry: "ghcr.io".to_string(),
+                path: "charts/whoami".to_string(),
+                tag: Some("5.2.0".to_string()),
+            }
+        );
+
+        let reference = ChartReference::parse("oci://localhost:5000/whoami");
+
+        assert!(reference.is_oci());
+        assert_eq!(reference.registry(), Some("localhost:5000"));
+        assert_eq!(reference.tag(), None);
+        assert_eq!(reference.to_string(), "oci://localhost:5000/whoami");
+
+        let reference = ChartReference::parse("oci://localhost:5000/whoami:1.0.0-rc.1");
+
+        assert_eq!(reference.tag(), Some("1.0.0-rc.1"));
+        assert_eq!(
+            reference.without_tag().to_string(),
+            "oci://localhost:5000/whoami"
+        );
+
+        let digest = "oci://ghcr.io/charts/whoami@sha256:abc";
+
+        assert_eq!(ChartReference::parse(digest).tag(), None);
+        assert_eq!(ChartReference::parse(digest).to_string(), digest);
+    }
+
+    #[test]
+    fn parse_other_references() {
+        assert_eq!(
+            ChartReference::parse("cowboysysop/whoami"),
+            ChartReference::Repository {
+                repo: "cowboysysop".to_string(),
+                chart: "whoami".to_string(),
+            }
+        );
+        assert_eq!(
+            ChartReference::parse("https://example.com/whoami-5.2.0.tgz"),
+            ChartReference::Url("https://example.com/whoami-5.2.0.tgz".to_string())
+        );
+        assert_eq!(
+            ChartReference::parse("./whoami"),
+            ChartReference::Path(PathBuf::from("./whoami"))
+        );
+        assert_eq!(
+            ChartReference::parse("charts/apps/whoami"),
+            ChartReference::Path(PathBuf::from("charts/apps/whoami"))
+        );
+        assert_eq!(
+            ChartReference::parse("dist/whoami-5.2.0.tgz"),
+            ChartReference::Path(PathBuf::from("dist/whoami-5.2.0.tgz"))
+        );
+        assert!(!ChartReference::parse("whoami").is_oci());
+    }
+
+    #[test]
+    fn parse_push_output() {
+        let output = "Pushed: ghcr.io/charts/whoami:5.2.0\nDigest: sha256:0123abcd\n";
+
+        assert_eq!(
+            parse_pushed_chart(output),
+            PushedChart {
+                reference: Some("ghcr.io/charts/whoami:5.2.0".to_string()),
+                digest: Some("sha256:0123abcd".to_string()),
+            }
+        );
+        assert_eq!(parse_pushed_chart(""), PushedChart::default());
+    }
+}
@@ -0,0 +1,540 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    args::HelmArgs,
+    redact::Redactor,
+    runner::{AsyncCommandRunner, CommandInvocation, CommandOutput, CommandRunner},
+};
+
+/// Placeholder for inline values temp file path, which is random on every run
+pub const VALUES_FILE_PLACEHOLDER: &str = "<values-file>";
+
+/// Helm invocation stored in fixture file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RecordedInvocation {
+    /// Redacted arguments, temp values file paths are replaced with placeholder
+    pub args: Vec<String>,
+    /// Redacted content of inline values files in the order of placeholders
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<serde_json::Value>,
+    /// Helm received secret input, the content itself isn't recorded
+    #[serde(default)]
+    pub stdin: bool,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl RecordedInvocation {
+    fn output(&self) -> CommandOutput {
+        CommandOutput {
+            exit_code: self.exit_code,
+            stdout: self.stdout.clone().into_bytes(),
+            stderr: self.stderr.clone().into_bytes(),
+        }
+    }
+}
+
+/// Arguments as they are stored in fixtures
+fn fixture_args(invocation: &CommandInvocation, redactor: &Redactor) -> Vec<String> {
+    invocation
+        .args
+        .iter()
+        .cloned()
+        .collect::<HelmArgs>()
+        .redacted(redactor)
+        .iter()
+        .map(|arg| {
+            let arg = arg.to_string_lossy().to_string();
+
+            if is_values_file(&arg) {
+                VALUES_FILE_PLACEHOLDER.to_string()
+            } else {
+                arg
+            }
+        })
+        .collect()
+}
+
+/// Content of inline values files, which exist while helm runs
+fn fixture_values(
+    invocation: &CommandInvocation,
+    redactor: &Redactor,
+) -> io::Result<Vec<serde_json::Value>> {
+    invocation
+        .args_lossy()
+        .iter()
+        .filter(|arg| is_values_file(arg))
+        .map(|path| {
+            let content = fs::read_to_string(path)?;
+
+            let values: serde_json::Value = serde_json::from_str(&content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            Ok(redactor.redact_json(&values))
+        })
+        .collect()
+}
+
+/// Inline values are passed through `helm-values-*.json` temp file
+fn is_values_file(arg: &str) -> bool {
+    Path::new(arg)
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .is_some_and(|file_name| {
+            file_name.starts_with("helm-values-") && file_name.ends_with(".json")
+        })
+}
+
+/// Runner wrapper which writes every helm invocation with its output to fixture
+/// directory: `0001-ls.json`, `0002-upgrade.json`, ...
+///
+/// Arguments, inline values, stdout and stderr are redacted with given redactor,
+/// stdin content isn't written. Clones share invocation counter.
+#[derive(Debug, Clone)]
+pub struct RecordingCommandRunner<R> {
+    runner: R,
+    dir: PathBuf,
+    redactor: Redactor,
+    counter: Arc<AtomicUsize>,
+}
+
+impl<R> RecordingCommandRunner<R> {
+    /// Record invocations of `runner` to `dir`, fixtures from previous recording are removed
+    pub fn new(runner: R, dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+
+        fs::create_dir_all(&dir)?;
+
+        for path in fixture_paths(&dir)? {
+            fs::remove_file(path)?;
+        }
+
+        Ok(Self {
+            runner,
+            dir,
+            redactor: Redactor::default(),
+            counter: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Set redactor for recorded arguments and output, usually the same as executor one
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    fn record(&self, invocation: &CommandInvocation, output: &CommandOutput) -> io::Result<()> {
+        let index = self.counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let redactor = match &invocation.stdin {
+            Some(stdin) => self.redactor.clone().secret(stdin.trim_end()),
+            None => self.redactor.clone(),
+        };
+
+        let recorded = RecordedInvocation {
+            args: fixture_args(invocation, &redactor),
+            values: fixture_values(invocation, &redactor)?,
+            stdin: invocation.stdin.is_some(),
+            exit_code: output.exit_code,
+            stdout: redactor.redact_text(&String::from_utf8_lossy(&output.stdout)),
+            stderr: redactor.redact_text(&String::from_utf8_lossy(&output.stderr)),
+        };
+
+        let subcommand: String = recorded
+            .args
+            .first()
+            .map(|subcommand| {
+                subcommand
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let content = serde_json::to_string_pretty(&recorded).map_err(io::Error::other)?;
+
+        fs::write(
+            self.dir.join(format!("{index:04}-{subcommand}.json")),
+            content,
+        )
+    }
+}
+
+impl<R: CommandRunner> CommandRunner for RecordingCommandRunner<R> {
+    fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        let output = self.runner.run(invocation)?;
+        self.record(invocation, &output)?;
+        Ok(output)
+    }
+}
+
+impl<R: AsyncCommandRunner> AsyncCommandRunner for RecordingCommandRunner<R> {
+    async fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        let output = self.runner.run(invocation).await?;
+        self.record(invocation, &output)?;
+        Ok(output)
+    }
+}
+
+/// Runner which serves fixtures written by `RecordingCommandRunner` in the recorded order.
+///
+/// Panics if invocation arguments or inline values differ from the recorded ones or there
+/// is no fixture left. Use `assert_replayed` at the end of test to check that every fixture was used.
+/// Clones share replay position.
+#[derive(Debug, Clone)]
+pub struct ReplayCommandRunner {
+    fixtures: Arc<Vec<(PathBuf, RecordedInvocation)>>,
+    redactor: Redactor,
+    position: Arc<AtomicUsize>,
+}
+
+impl ReplayCommandRunner {
+    /// Load fixtures from directory
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let fixtures = fixture_paths(dir.as_ref())?
+            .into_iter()
+            .map(|path| {
+                let content = fs::read_to_string(&path)?;
+
+                let recorded: RecordedInvocation = serde_json::from_str(&content).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid fixture '{}': {e}", path.display()),
+                    )
+                })?;
+
+                Ok((path, recorded))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            fixtures: Arc::new(fixtures),
+            redactor: Redactor::default(),
+            position: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Set redactor which was used for recording, arguments are compared redacted
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Number of fixtures which weren't replayed yet
+    pub fn remaining(&self) -> usize {
+        self.fixtures
+            .len()
+            .saturating_sub(self.position.load(Ordering::SeqCst))
+    }
+
+    /// Panic if some fixtures weren't replayed
+    pub fn assert_replayed(&self) {
+        let position = self.position.load(Ordering::SeqCst);
+
+        if let Some((path, recorded)) = self.fixtures.get(position) {
+            panic!(
+                "{} recorded helm invocation(s) weren't replayed, next one '{}': helm {}",
+                self.remaining(),
+                path.display(),
+                recorded.args.join(" ")
+            );
+        }
+    }
+
+    fn replay(&self, invocation: &CommandInvocation) -> CommandOutput {
+        let redactor = match &invocation.stdin {
+            Some(stdin) => self.redactor.clone().secret(stdin.trim_end()),
+            None => self.redactor.clone(),
+        };
+
+        let args = fixture_args(invocation, &redactor);
+        let position = self.position.fetch_add(1, Ordering::SeqCst);
+
+        let Some((path, recorded)) = self.fixtures.get(position) else {
+            panic!(
+                "unexpected helm invocation #{}, only {} recorded: helm {}",
+                position + 1,
+                self.fixtures.len(),
+                args.join(" ")
+            );
+        };
+
+        if recorded.args != args || recorded.stdin != invocation.stdin.is_some() {
+            panic!(
+                "helm invocation #{} differs from fixture '{}'\n  recorded: helm {}\n  actual:   helm {}",
+                position + 1,
+                path.display(),
+                recorded.args.join(" "),
+                args.join(" ")
+            );
+        }
+
+        let values = fixture_values(invocation, &redactor).unwrap_or_else(|e| {
+            panic!(
+                "unable to read inline values of helm invocation #{}: {e}",
+                position + 1
+            )
+        });
+
+        if recorded.values != values {
+            panic!(
+                "inline values of helm invocation #{} differ from fixture '{}'\n  recorded: {}\n  actual:   {}",
+                position + 1,
+                path.display(),
+                serde_json::Value::from(recorded.values.clone()),
+                serde_json::Value::from(values)
+            );
+        }
+
+        recorded.output()
+    }
+}
+
+impl CommandRunner for ReplayCommandRunner {
+    fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        Ok(self.replay(invocation))
+    }
+}
+
+impl AsyncCommandRunner for ReplayCommandRunner {
+    async fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        Ok(self.replay(invocation))
+    }
+}
+
+/// Fixture files `NNNN-<subcommand>.json` in recorded order
+fn fixture_paths(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .is_some_and(|file_name| {
+                    file_name.ends_with(".json")
+                        && file_name
+                            .get(..4)
+                            .is_some_and(|index| index.chars().all(|c| c.is_ascii_digit()))
+                        && file_name.get(4..).is_some_and(|rest| rest.starts_with('-'))
+                })
+        })
+        .collect();
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod replay_tests {
+    use serde_json::json;
+
+    use crate::{
+        blocking::{DefaultHelmExecutor, HelmExecutor},
+        error::HelmWrapperError,
+        redact::Redactor,
+        replay::{RecordedInvocation, RecordingCommandRunner, ReplayCommandRunner},
+        repo::{RepoAddOptions, RepoCredentials},
+        request::InstallOrUpgradeRequest,
+        runner::{CommandOutput, CommandRunner, ScriptedCommandRunner},
+    };
+
+    fn redactor() -> Redactor {
+        Redactor::new().sensitive_key("password")
+    }
+
+    fn request() -> InstallOrUpgradeRequest {
+        InstallOrUpgradeRequest::new(
+            "apps".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .values_json(json!({"replicaCount": 2}))
+        .set("db.password", "qwerty")
+    }
+
+    fn repo_options() -> RepoAddOptions {
+        RepoAddOptions {
+            credentials: Some(RepoCredentials::new("admin", "p4ss")),
+            ..RepoAddOptions::default()
+        }
+    }
+
+    /// Run the same helm calls with given executor
+    fn run_calls<R: CommandRunner>(
+        executor: &DefaultHelmExecutor<R>,
+    ) -> Result<(), HelmWrapperError> {
+        executor.repo_add(
+            &"private".parse().unwrap(),
+            &"https://charts.example.com".parse().unwrap(),
+            &repo_options(),
+        )?;
+
+        let release = executor.install_or_upgrade_with(&request())?;
+        assert_eq!(release.version, 1);
+
+        executor.uninstall(&"apps".parse().unwrap(), &"whoami".parse().unwrap())
+    }
+
+    fn record(dir: &std::path::Path) {
+        let scripted = ScriptedCommandRunner::new();
+        scripted
+            .push_output(CommandOutput::ok("\"private\" has been added, p4ss\n"))
+            .push_output(CommandOutput::ok(
+                r#"{"name":"whoami","namespace":"apps","version":1,"info":{"status":"deployed"}}"#,
+            ))
+            .push_output(CommandOutput::failure(
+                1,
+                "Error: uninstall: Release not loaded: whoami: release: not found",
+            ));
+
+        let runner = RecordingCommandRunner::new(scripted, dir)
+            .unwrap()
+            .with_redactor(redactor());
+
+        let executor = DefaultHelmExecutor::new()
+            .with_redactor(redactor())
+            .with_runner(runner);
+
+        assert!(matches!(
+            run_calls(&executor),
+            Err(HelmWrapperError::ReleaseNotFound(_))
+        ));
+    }
+
+    fn replay_executor(dir: &std::path::Path) -> DefaultHelmExecutor<ReplayCommandRunner> {
+        let runner = ReplayCommandRunner::load(dir)
+            .unwrap()
+            .with_redactor(redactor());
+
+        DefaultHelmExecutor::new()
+            .with_redactor(redactor())
+            .with_runner(runner)
+    }
+
+    #[test]
+    fn recorded_invocations_are_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        record(dir.path());
+
+        let mut files: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+
+        assert_eq!(
+            files,
+            vec!["0001-repo.json", "0002-upgrade.json", "0003-uninstall.json"]
+        );
+
+        let repo_add: RecordedInvocation = serde_json::from_str(
+            &std::fs::read_to_string(dir.path().join("0001-repo.json")).unwrap(),
+        )
+        .unwrap();
+
+        assert!(repo_add.stdin);
+        assert_eq!(repo_add.stdout, "\"private\" has been added, ***\n");
+
+        let upgrade = std::fs::read_to_string(dir.path().join("0002-upgrade.json")).unwrap();
+
+        assert!(upgrade.contains("<values-file>"));
+        assert!(upgrade.contains("\"replicaCount\": 2"));
+        assert!(upgrade.contains("db.password=***"));
+        assert!(!upgrade.contains("qwerty"));
+        assert!(!upgrade.contains("helm-values-"));
+
+        let executor = replay_executor(dir.path());
+
+        assert!(matches!(
+            run_calls(&executor),
+            Err(HelmWrapperError::ReleaseNotFound(_))
+        ));
+
+        executor.get_runner().assert_replayed();
+    }
+
+    #[test]
+    fn rerecording_removes_previous_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+
+        std::fs::write(dir.path().join("0009-ls.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("README.md"), "fixtures").unwrap();
+
+        record(dir.path());
+
+        assert_eq!(
+            ReplayCommandRunner::load(dir.path()).unwrap().remaining(),
+            3
+        );
+        assert!(dir.path().join("README.md").exists());
+    }
+
+    #[test]
+    #[should_panic(expected = "differs from fixture")]
+    fn different_invocation_panics() {
+        let dir = tempfile::tempdir().unwrap();
+
+        record(dir.path());
+
+        let _ = replay_executor(dir.path()).list(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "inline values of helm invocation #2 differ from fixture")]
+    fn different_inline_values_panic() {
+        let dir = tempfile::tempdir().unwrap();
+
+        record(dir.path());
+
+        let executor = replay_executor(dir.path());
+
+        executor
+            .repo_add(
+                &"private".parse().unwrap(),
+                &"https://charts.example.com".parse().unwrap(),
+                &repo_options(),
+            )
+            .unwrap();
+
+        let request = InstallOrUpgradeRequest::new(
+            "apps".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+        .values_json(json!({"replicaCount": 3}))
+        .set("db.password", "qwerty");
+
+        let _ = executor.install_or_upgrade_with(&request);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected helm invocation #1, only 0 recorded")]
+    fn unexpected_invocation_panics() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let _ = replay_executor(dir.path()).list(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "weren't replayed")]
+    fn not_replayed_fixtures_panic() {
+        let dir = tempfile::tempdir().unwrap();
+
+        record(dir.path());
+
+        replay_executor(dir.path()).get_runner().assert_replayed();
+    }
+}
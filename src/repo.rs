@@ -0,0 +1,50 @@
+use std::{fmt, path::PathBuf};
+
+use serde::Deserialize;
+
+/// Chart repository from `helm repo list`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HelmRepository {
+    pub name: String,
+    pub url: String,
+}
+
+/// Repository credentials, password is passed to helm through stdin and never logged
+#[derive(Clone, PartialEq, Eq)]
+pub struct RepoCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl RepoCredentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+impl fmt::Debug for RepoCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepoCredentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// Options for `helm repo add`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoAddOptions {
+    /// Username (`--username`) and password (`--password-stdin`)
+    pub credentials: Option<RepoCredentials>,
+    /// Pass credentials to all domains (`--pass-credentials`)
+    pub pass_credentials: bool,
+    /// Verify certificates of HTTPS-enabled servers using this CA bundle (`--ca-file`)
+    pub ca_file: Option<PathBuf>,
+    /// Skip tls certificate checks for the repository (`--insecure-skip-tls-verify`)
+    pub insecure_skip_tls_verify: bool,
+    /// Replace (overwrite) the repo if it already exists (`--force-update`)
+    pub force_update: bool,
+}
@@ -0,0 +1,527 @@
+use std::{collections::BTreeMap, fs::File, io, path::PathBuf};
+
+use non_blank_string_rs::NonBlankString;
+use serde::Serialize;
+
+use crate::{
+    error::HelmWrapperError,
+    overrides::{escape_value, OverrideValue, ValuePath},
+};
+
+/// How override value is passed to helm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueOverrideKind {
+    /// `--set`, helm infers value type
+    Set,
+    /// `--set-string`, value is always a string
+    SetString,
+    /// `--set-file`, value is read from file
+    SetFile,
+    /// `--set-json`, value is JSON
+    SetJson,
+}
+
+impl ValueOverrideKind {
+    pub fn flag(&self) -> &'static str {
+        match self {
+            ValueOverrideKind::Set => "--set",
+            ValueOverrideKind::SetString => "--set-string",
+            ValueOverrideKind::SetFile => "--set-file",
+            ValueOverrideKind::SetJson => "--set-json",
+        }
+    }
+}
+
+/// Single chart value override, passed to helm as `<FLAG> KEY=VALUE`.
+/// Key and value are in helm syntax, see [`OverrideValue`] for escaped ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueOverride {
+    pub kind: ValueOverrideKind,
+    pub key: String,
+    pub value: String,
+}
+
+/// Source of chart values, passed to helm with `-f`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValuesSource {
+    /// Path to values file
+    File(PathBuf),
+    /// In-memory values, passed to helm through temp file readable by owner only.
+    /// Content is logged only in unsafe mode.
+    Inline(serde_json::Value),
+}
+
+/// Dry run mode for `--dry-run`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRun {
+    /// Render templates without contacting the cluster
+    Client,
+    /// Render templates and validate them against the cluster
+    Server,
+}
+
+/// Parameters of `helm upgrade --install`.
+///
+/// Built from required namespace, release and chart names, other options are set
+/// with builder methods:
+///
+/// ```
+/// use helm_wrapper_rs::request::InstallOrUpgradeRequest;
+///
+/// let request = InstallOrUpgradeRequest::new(
+///     "whoami".parse().unwrap(),
+///     "whoami".parse().unwrap(),
+///     "cowboysysop/whoami".parse().unwrap(),
+/// )
+/// .chart_version("5.2.0".parse().unwrap())
+/// .values_file("values.yaml")
+/// .set("replicaCount", "2")
+/// .atomic(true)
+/// .create_namespace(true);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstallOrUpgradeRequest {
+    pub namespace: NonBlankString,
+    pub release_name: NonBlankString,
+    pub chart_name: NonBlankString,
+    pub chart_version: Option<NonBlankString>,
+    /// Values sources in helm precedence order, the last one wins
+    pub values: Vec<ValuesSource>,
+    /// Value overrides in the order they are passed to helm
+    pub value_overrides: Vec<ValueOverride>,
+    pub atomic: bool,
+    pub wait: bool,
+    pub wait_for_jobs: bool,
+    pub create_namespace: bool,
+    pub description: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    pub reuse_values: bool,
+    pub reset_values: bool,
+    pub dry_run: Option<DryRun>,
+    /// Any other options for helm
+    pub helm_options: Vec<NonBlankString>,
+}
+
+impl InstallOrUpgradeRequest {
+    pub fn new(
+        namespace: NonBlankString,
+        release_name: NonBlankString,
+        chart_name: NonBlankString,
+    ) -> Self {
+        Self {
+            namespace,
+            release_name,
+            chart_name,
+            chart_version: None,
+            values: vec![],
+            value_overrides: vec![],
+            atomic: false,
+            wait: false,
+            wait_for_jobs: false,
+            create_namespace: false,
+            description: None,
+            labels: BTreeMap::new(),
+            reuse_values: false,
+            reset_values: false,
+            dry_run: None,
+            helm_options: vec![],
+        }
+    }
+
+    /// Request equal to positional `install_or_upgrade` parameters.
+    /// Override keys are helm paths, values are escaped.
+    #[cfg(any(feature = "blocking", feature = "nonblocking"))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn from_args(
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        chart_name: &NonBlankString,
+        chart_version: Option<&NonBlankString>,
+        values_overrides: Option<&[(NonBlankString, String)]>,
+        values_file: Option<&std::path::Path>,
+        helm_options: Option<&Vec<NonBlankString>>,
+    ) -> Self {
+        let mut request = Self::new(namespace.clone(), release_name.clone(), chart_name.clone());
+
+        request.chart_version = chart_version.cloned();
+
+        if let Some(values_file) = values_file {
+            request = request.values_file(values_file);
+        }
+
+        if let Some(overrides) = values_overrides {
+            for (k, v) in overrides {
+                request = request.set(k.to_string(), v.to_string());
+            }
+        }
+
+        if let Some(helm_options) = helm_options {
+            request.helm_options = helm_options.clone();
+        }
+
+        request
+    }
+
+    /// Check that labels can be passed to helm and every values file exists and is readable,
+    /// so request fails before helm is invoked
+    pub fn validate(&self) -> Result<(), HelmWrapperError> {
+        // helm reads `--labels` as comma separated `key=value` list without escaping
+        let separators = [',', '='];
+
+        for (key, value) in &self.labels {
+            if key.is_empty() || key.contains(separators) || value.contains(separators) {
+                return Err(HelmWrapperError::InvalidLabel(key.clone()));
+            }
+        }
+
+        validate_values(&self.values)
+    }
+
+    /// Chart version. For example: 1.2.3
+    pub fn chart_version(mut self, chart_version: NonBlankString) -> Self {
+        self.chart_version = Some(chart_version);
+        self
+    }
+
+    /// Add values file (`-f`), values added later take precedence
+    pub fn values_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.values.push(ValuesSource::File(path.into()));
+        self
+    }
+
+    /// Add values files in precedence order, e.g. `values.yaml`, `values-prod.yaml`
+    pub fn values_files<P: Into<PathBuf>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.values.extend(
+            paths
+                .into_iter()
+                .map(|path| ValuesSource::File(path.into())),
+        );
+        self
+    }
+
+    /// Add in-memory values, e.g. serde struct. Values added later take precedence.
+    pub fn values<T: Serialize>(self, values: &T) -> Result<Self, HelmWrapperError> {
+        let values =
+            serde_json::to_value(values).map_err(HelmWrapperError::ValuesSerializationError)?;
+        Ok(self.values_json(values))
+    }
+
+    /// Add in-memory JSON values. Values added later take precedence.
+    pub fn values_json(mut self, values: serde_json::Value) -> Self {
+        self.values.push(ValuesSource::Inline(values));
+        self
+    }
+
+    /// Typed override, key and value are escaped for helm, e.g. dots in keys are kept literally
+    pub fn set_value(mut self, path: &ValuePath, value: impl Into<OverrideValue>) -> Self {
+        self.value_overrides.push(value.into().to_override(path));
+        self
+    }
+
+    /// `--set KEY=VALUE`, helm infers value type.
+    ///
+    /// Key is helm path: dots separate nested keys, `[N]` is list index, use `set_value`
+    /// for literal keys. Value is escaped, commas and leading `{` are kept literally.
+    pub fn set(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.value_override(ValueOverrideKind::Set, key, escape_value(&value.into()))
+    }
+
+    /// `--set-string KEY=VALUE`, key is helm path and value is escaped like in `set`
+    pub fn set_string(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.value_override(
+            ValueOverrideKind::SetString,
+            key,
+            escape_value(&value.into()),
+        )
+    }
+
+    /// `--set-file KEY=PATH`, key is helm path and file path is escaped like value in `set`
+    pub fn set_file(self, key: impl Into<String>, path: impl Into<String>) -> Self {
+        self.value_override(ValueOverrideKind::SetFile, key, escape_value(&path.into()))
+    }
+
+    /// `--set-json KEY=JSON`, key is helm path and JSON is passed as is
+    pub fn set_json(self, key: impl Into<String>, json: impl Into<String>) -> Self {
+        self.value_override(ValueOverrideKind::SetJson, key, json)
+    }
+
+    fn value_override(
+        mut self,
+        kind: ValueOverrideKind,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.value_overrides.push(ValueOverride {
+            kind,
+            key: key.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// `--atomic`, rollback changes if upgrade fails
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// `--wait`, wait until all resources are in ready state
+    pub fn wait(mut self, wait: bool) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// `--wait-for-jobs`, wait until all jobs are completed
+    pub fn wait_for_jobs(mut self, wait_for_jobs: bool) -> Self {
+        self.wait_for_jobs = wait_for_jobs;
+        self
+    }
+
+    /// `--create-namespace`
+    pub fn create_namespace(mut self, create_namespace: bool) -> Self {
+        self.create_namespace = create_namespace;
+        self
+    }
+
+    /// `--description`, custom release description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// `--labels KEY=VALUE`, label added to release metadata.
+    /// Key and value can't contain `,` and `=`, such request fails validation.
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// `--reuse-values`, reuse values of the last release and merge overrides
+    pub fn reuse_values(mut self, reuse_values: bool) -> Self {
+        self.reuse_values = reuse_values;
+        self
+    }
+
+    /// `--reset-values`, reset values to the ones built into the chart
+    pub fn reset_values(mut self, reset_values: bool) -> Self {
+        self.reset_values = reset_values;
+        self
+    }
+
+    /// `--dry-run=client|server`, simulate install or upgrade
+    pub fn dry_run(mut self, dry_run: DryRun) -> Self {
+        self.dry_run = Some(dry_run);
+        self
+    }
+
+    /// Any other option for helm, for example `--skip-crds`
+    pub fn helm_option(mut self, helm_option: NonBlankString) -> Self {
+        self.helm_options.push(helm_option);
+        self
+    }
+}
+
+/// Check that every values file exists and is readable
+pub(crate) fn validate_values(values: &[ValuesSource]) -> Result<(), HelmWrapperError> {
+    for source in values {
+        match source {
+            ValuesSource::File(path) => {
+                let readable = File::open(path).and_then(|file| {
+                    if file.metadata()?.is_file() {
+                        Ok(())
+                    } else {
+                        Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file"))
+                    }
+                });
+
+                if let Err(e) = readable {
+                    return Err(HelmWrapperError::ValuesFileError {
+                        path: path.clone(),
+                        source: e,
+                    });
+                }
+            }
+            ValuesSource::Inline(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod request_tests {
+    use std::path::Path;
+
+    use crate::{
+        error::HelmWrapperError,
+        overrides::{OverrideValue, ValuePath},
+        request::{InstallOrUpgradeRequest, ValueOverride, ValueOverrideKind, ValuesSource},
+    };
+
+    fn request() -> InstallOrUpgradeRequest {
+        InstallOrUpgradeRequest::new(
+            "whoami".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "cowboysysop/whoami".parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn validate_existing_values_files() {
+        let request = request().values_files([
+            Path::new("test-data").join("whoami-values.yml"),
+            Path::new("test-data").join("whoami-unknown-values.yml"),
+        ]);
+
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_missing_values_file() {
+        let missing_file = Path::new("test-data").join("missing-values.yml");
+
+        let request = request()
+            .values_file(Path::new("test-data").join("whoami-values.yml"))
+            .values_file(&missing_file);
+
+        match request.validate() {
+            Err(HelmWrapperError::ValuesFileError { path, .. }) => assert_eq!(path, missing_file),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn validate_labels() {
+        assert!(request().label("team", "platform").validate().is_ok());
+
+        for (key, value) in [
+            ("team", "a,owner=b"),
+            ("team", "a=b"),
+            ("team,owner", "a"),
+            ("team=owner", "a"),
+            ("", "a"),
+        ] {
+            match request().label(key, value).validate() {
+                Err(HelmWrapperError::InvalidLabel(invalid)) => assert_eq!(invalid, key),
+                other => panic!("unexpected result for '{key}={value}': {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_directory_as_values_file() {
+        let request = request().values_file("test-data");
+
+        assert!(matches!(
+            request.validate(),
+            Err(HelmWrapperError::ValuesFileError { .. })
+        ));
+    }
+
+    #[test]
+    fn serializable_values_become_inline_source() {
+        #[derive(serde::Serialize)]
+        struct Values {
+            #[serde(rename = "replicaCount")]
+            replica_count: u8,
+        }
+
+        let request = request().values(&Values { replica_count: 2 }).unwrap();
+
+        assert_eq!(
+            request.values,
+            vec![ValuesSource::Inline(serde_json::json!({"replicaCount": 2}))]
+        );
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn typed_overrides_are_escaped_in_order() {
+        let request = request()
+            .set("image.tag", "1.0")
+            .set_value(
+                &ValuePath::new("podAnnotations").key("prometheus.io/scrape"),
+                "true",
+            )
+            .set_value(
+                &ValuePath::new("args").index(0),
+                OverrideValue::Inferred("--a=1,--b=2".to_string()),
+            );
+
+        assert_eq!(
+            request.value_overrides,
+            vec![
+                ValueOverride {
+                    kind: ValueOverrideKind::Set,
+                    key: "image.tag".to_string(),
+                    value: "1.0".to_string(),
+                },
+                ValueOverride {
+                    kind: ValueOverrideKind::SetString,
+                    key: r"podAnnotations.prometheus\.io/scrape".to_string(),
+                    value: "true".to_string(),
+                },
+                ValueOverride {
+                    kind: ValueOverrideKind::Set,
+                    key: "args[0]".to_string(),
+                    value: r"--a=1\,--b=2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_overrides_escape_values() {
+        let request = request()
+            .set("hosts", "a.example.com,b.example.com")
+            .set_string("args", "{--a,--b}")
+            .set_file("certs[0]", r"C:\certs\a,b.pem")
+            .set_json("resources", r#"{"limits":{"cpu":"100m","memory":"64Mi"}}"#);
+
+        let overrides: Vec<(&str, &str)> = request
+            .value_overrides
+            .iter()
+            .map(|value_override| (value_override.key.as_str(), value_override.value.as_str()))
+            .collect();
+
+        assert_eq!(
+            overrides,
+            vec![
+                ("hosts", r"a.example.com\,b.example.com"),
+                ("args", r"\{--a\,--b}"),
+                ("certs[0]", r"C:\\certs\\a\,b.pem"),
+                ("resources", r#"{"limits":{"cpu":"100m","memory":"64Mi"}}"#),
+            ]
+        );
+    }
+
+    #[cfg(any(feature = "blocking", feature = "nonblocking"))]
+    #[test]
+    fn positional_overrides_keep_order() {
+        use non_blank_string_rs::NonBlankString;
+
+        let overrides: Vec<(NonBlankString, String)> = vec![
+            ("a.b".parse().unwrap(), "1".to_string()),
+            ("a".parse().unwrap(), "null".to_string()),
+            ("a.c".parse().unwrap(), "2".to_string()),
+        ];
+
+        let request = InstallOrUpgradeRequest::from_args(
+            &"whoami".parse().unwrap(),
+            &"whoami".parse().unwrap(),
+            &"cowboysysop/whoami".parse().unwrap(),
+            None,
+            Some(overrides.as_slice()),
+            None,
+            None,
+        );
+
+        let keys: Vec<&str> = request
+            .value_overrides
+            .iter()
+            .map(|value_override| value_override.key.as_str())
+            .collect();
+
+        assert_eq!(keys, vec!["a.b", "a", "a.c"]);
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::VecDeque,
+    ffi::OsString,
+    future::Future,
+    io::{self, Write},
+    process::{Command, Output, Stdio},
+    sync::{Arc, Mutex},
+};
+
+/// Process to run: program, argv, extra environment and stdin content
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CommandInvocation {
+    pub program: String,
+    pub args: Vec<OsString>,
+    /// Environment variables set in addition to inherited ones
+    pub env: Vec<(OsString, OsString)>,
+    /// Content written to process stdin, e.g. password for `--password-stdin`
+    pub stdin: Option<String>,
+}
+
+impl CommandInvocation {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Arguments converted to strings, invalid UTF-8 is replaced
+    pub fn args_lossy(&self) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+}
+
+/// Exit status and output of finished process
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CommandOutput {
+    /// Exit code, `None` if process was terminated by signal
+    pub exit_code: Option<i32>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl CommandOutput {
+    /// Successful output with given stdout
+    pub fn ok(stdout: impl Into<Vec<u8>>) -> Self {
+        Self {
+            exit_code: Some(0),
+            stdout: stdout.into(),
+            stderr: vec![],
+        }
+    }
+
+    /// Failed output with given exit code and stderr
+    pub fn failure(exit_code: i32, stderr: impl Into<Vec<u8>>) -> Self {
+        Self {
+            exit_code: Some(exit_code),
+            stdout: vec![],
+            stderr: stderr.into(),
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+impl From<Output> for CommandOutput {
+    fn from(output: Output) -> Self {
+        Self {
+            exit_code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+}
+
+/// Runs processes for blocking executor
+pub trait CommandRunner {
+    fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput>;
+}
+
+/// Runs processes for nonblocking executor
+pub trait AsyncCommandRunner: Send + Sync {
+    fn run(
+        &self,
+        invocation: &CommandInvocation,
+    ) -> impl Future<Output = io::Result<CommandOutput>> + Send;
+}
+
+/// Runner which spawns real processes, default for executors.
+///
+/// Nonblocking flavour kills the process when the returned future is dropped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        let mut process = Command::new(&invocation.program);
+        process
+            .args(&invocation.args)
+            .envs(invocation.env.iter().map(|(key, value)| (key, value)));
+
+        let output = match &invocation.stdin {
+            Some(stdin) => {
+                let mut child = process
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+
+                if let Some(mut child_stdin) = child.stdin.take() {
+                    child_stdin.write_all(stdin.as_bytes())?;
+                }
+
+                child.wait_with_output()?
+            }
+            None => process.output()?,
+        };
+
+        Ok(output.into())
+    }
+}
+
+#[cfg(feature = "nonblocking")]
+impl AsyncCommandRunner for SystemCommandRunner {
+    async fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        use tokio::{io::AsyncWriteExt, process::Command};
+
+        let mut process = Command::new(&invocation.program);
+        process
+            .args(&invocation.args)
+            .envs(invocation.env.iter().map(|(key, value)| (key, value)))
+            .kill_on_drop(true);
+
+        let output = match &invocation.stdin {
+            Some(stdin) => {
+                let mut child = process
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+
+                if let Some(mut child_stdin) = child.stdin.take() {
+                    child_stdin.write_all(stdin.as_bytes()).await?;
+                }
+
+                child.wait_with_output().await?
+            }
+            None => process.output().await?,
+        };
+
+        Ok(output.into())
+    }
+}
+
+/// In-memory runner for unit tests: returns queued responses in order and
+/// records every invocation. Clones share the same queue and records.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedCommandRunner(Arc<Mutex<ScriptedState>>);
+
+#[derive(Debug, Default)]
+struct ScriptedState {
+    responses: VecDeque<io::Result<CommandOutput>>,
+    invocations: Vec<CommandInvocation>,
+}
+
+impl ScriptedCommandRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue process output
+    pub fn push_output(&self, output: CommandOutput) -> &Self {
+        self.state().responses.push_back(Ok(output));
+        self
+    }
+
+    /// Queue error of process spawn, e.g. helm executable not found
+    pub fn push_error(&self, error: io::Error) -> &Self {
+        self.state().responses.push_back(Err(error));
+        self
+    }
+
+    /// Invocations received so far
+    pub fn invocations(&self) -> Vec<CommandInvocation> {
+        self.state().invocations.clone()
+    }
+
+    /// Number of queued responses which weren't consumed yet
+    pub fn pending(&self) -> usize {
+        self.state().responses.len()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ScriptedState> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        let mut state = self.state();
+        state.invocations.push(invocation.clone());
+
+        state.responses.pop_front().unwrap_or_else(|| {
+            Err(io::Error::other(format!(
+                "no scripted response for '{} {}'",
+                invocation.program,
+                invocation.args_lossy().join(" ")
+            )))
+        })
+    }
+}
+
+impl CommandRunner for ScriptedCommandRunner {
+    fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        self.next(invocation)
+    }
+}
+
+impl AsyncCommandRunner for ScriptedCommandRunner {
+    async fn run(&self, invocation: &CommandInvocation) -> io::Result<CommandOutput> {
+        self.next(invocation)
+    }
+}
+
+#[cfg(test)]
+mod runner_tests {
+    use std::io;
+
+    use crate::runner::{CommandInvocation, CommandOutput, CommandRunner, ScriptedCommandRunner};
+
+    #[test]
+    fn scripted_runner_returns_responses_in_order() {
+        let runner = ScriptedCommandRunner::new();
+        runner
+            .push_output(CommandOutput::ok("[]"))
+            .push_output(CommandOutput::failure(1, "Error: boom"))
+            .push_error(io::Error::new(io::ErrorKind::NotFound, "helm not found"));
+
+        let mut invocation = CommandInvocation::new("helm");
+        invocation.args = vec!["list".into()];
+
+        let clone = runner.clone();
+
+        assert_eq!(clone.run(&invocation).unwrap(), CommandOutput::ok("[]"));
+
+        let output = runner.run(&invocation).unwrap();
+        assert!(!output.success());
+        assert_eq!(output.stderr, b"Error: boom");
+
+        let error = runner.run(&invocation).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        let error = runner.run(&invocation).unwrap_err();
+        assert_eq!(error.to_string(), "no scripted response for 'helm list'");
+
+        assert_eq!(runner.pending(), 0);
+        assert_eq!(clone.invocations().len(), 4);
+        assert_eq!(runner.invocations()[0].args_lossy(), vec!["list"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn system_runner_passes_stdin_and_env() {
+        use crate::runner::SystemCommandRunner;
+
+        let invocation = CommandInvocation {
+            program: "sh".to_string(),
+            args: vec!["-c".into(), "cat; printf \" $GREETING\"; exit 3".into()],
+            env: vec![("GREETING".into(), "hello".into())],
+            stdin: Some("secret".to_string()),
+        };
+
+        let output = SystemCommandRunner.run(&invocation).unwrap();
+
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stdout, b"secret hello");
+    }
+}
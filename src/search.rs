@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+
+use non_blank_string_rs::NonBlankString;
+use semver::Version;
+use serde::Deserialize;
+
+use crate::HelmListItem;
+
+/// Chart version from `helm search repo`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChartSearchResult {
+    /// Chart name with repository, e.g. `cowboysysop/whoami`
+    pub name: String,
+    pub version: String,
+    pub app_version: String,
+    pub description: String,
+}
+
+impl ChartSearchResult {
+    /// Chart name without repository, e.g. `whoami`
+    pub fn chart_name(&self) -> &str {
+        self.name
+            .rsplit_once('/')
+            .map(|(_repo, chart_name)| chart_name)
+            .unwrap_or(&self.name)
+    }
+}
+
+/// Split chart of installed release (`whoami-5.2.0`) into chart name and version.
+/// Both name and version may contain dashes, e.g. `my-app-1.0.0-rc.1`.
+pub fn split_chart_version(chart: &str) -> Option<(&str, &str)> {
+    chart
+        .match_indices('-')
+        .map(|(index, _)| (&chart[..index], &chart[index + 1..]))
+        .find(|(name, version)| !name.is_empty() && Version::parse(version).is_ok())
+}
+
+/// Keyword to search release chart versions, e.g. `whoami` for `whoami-5.2.0`
+pub fn chart_keyword(release: &HelmListItem) -> Option<NonBlankString> {
+    let (chart_name, _version) = split_chart_version(&release.chart)?;
+    chart_name.parse().ok()
+}
+
+/// Versions of release chart which are newer than the deployed one, newest first.
+/// Results of other charts and versions which aren't valid semver are skipped.
+pub fn newer_versions(
+    release: &HelmListItem,
+    results: &[ChartSearchResult],
+) -> Vec<ChartSearchResult> {
+    let Some((chart_name, version)) = split_chart_version(&release.chart) else {
+        return vec![];
+    };
+
+    let Ok(current) = Version::parse(version) else {
+        return vec![];
+    };
+
+    let mut newer: Vec<(Version, ChartSearchResult)> = results
+        .iter()
+        .filter(|result| result.chart_name() == chart_name)
+        .filter_map(|result| {
+            Version::parse(&result.version)
+                .ok()
+                .filter(|version| *version > current)
+                .map(|version| (version, result.clone()))
+        })
+        .collect();
+
+    newer.sort_by(|(a, a_result), (b, b_result)| match b.cmp(a) {
+        Ordering::Equal => a_result.name.cmp(&b_result.name),
+        ordering => ordering,
+    });
+
+    newer.into_iter().map(|(_version, result)| result).collect()
+}
+
+#[cfg(test)]
+mod search_tests {
+    use crate::{
+        search::{newer_versions, split_chart_version, ChartSearchResult},
+        HelmDeployStatus, HelmListItem,
+    };
+
+    fn release(chart: &str) -> HelmListItem {
+        HelmListItem {
+            name: "whoami".to_string(),
+            namespace: "whoami".to_string(),
+            revision: "1".to_string(),
+            updated: String::new(),
+            status: HelmDeployStatus::Deployed,
+            chart: chart.to_string(),
+            app_version: "1.10.3".to_string(),
+        }
+    }
+
+    fn result(name: &str, version: &str) -> ChartSearchResult {
+        ChartSearchResult {
+            name: name.to_string(),
+            version: version.to_string(),
+            app_version: String::new(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn split_chart_into_name_and_version() {
+        assert_eq!(
+            split_chart_version("whoami-5.2.0"),
+            Some(("whoami", "5.2.0"))
+        );
+        assert_eq!(
+            split_chart_version("my-app-1.0.0-rc.1"),
+            Some(("my-app", "1.0.0-rc.1"))
+        );
+        assert_eq!(
+            split_chart_version("cert-manager-v1.14.0"),
+            None,
+            "'v' prefix is not semver"
+        );
+        assert_eq!(split_chart_version("whoami"), None);
+        assert_eq!(split_chart_version("-1.0.0"), None);
+    }
+
+    #[test]
+    fn newer_versions_of_same_chart_newest_first() {
+        let results = vec![
+            result("cowboysysop/whoami", "5.2.0"),
+            result("cowboysysop/whoami", "5.10.0"),
+            result("cowboysysop/whoami", "5.3.1"),
+            result("cowboysysop/whoami", "5.1.0"),
+            result("cowboysysop/whoami-extra", "9.0.0"),
+            result("mirror/whoami", "5.3.1"),
+            result("cowboysysop/whoami", "latest"),
+        ];
+
+        let newer = newer_versions(&release("whoami-5.2.0"), &results);
+
+        assert_eq!(
+            newer,
+            vec![
+                result("cowboysysop/whoami", "5.10.0"),
+                result("cowboysysop/whoami", "5.3.1"),
+                result("mirror/whoami", "5.3.1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn release_of_prerelease_version() {
+        let results = vec![
+            result("repo/app", "1.0.0"),
+            result("repo/app", "1.0.0-rc.1"),
+        ];
+
+        assert_eq!(
+            newer_versions(&release("app-1.0.0-rc.1"), &results),
+            vec![result("repo/app", "1.0.0")]
+        );
+    }
+
+    #[test]
+    fn unknown_chart_version_has_no_newer_versions() {
+        let results = vec![result("repo/app", "1.0.0")];
+
+        assert!(newer_versions(&release("app"), &results).is_empty());
+    }
+}
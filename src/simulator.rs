@@ -0,0 +1,910 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use non_blank_string_rs::NonBlankString;
+use semver::{Version, VersionReq};
+
+use crate::{
+    error::{HelmCommandFailure, HelmWrapperError},
+    registry::ChartReference,
+    repo::{HelmRepository, RepoAddOptions},
+    request::{InstallOrUpgradeRequest, ValuesSource},
+    search::{split_chart_version, ChartSearchResult},
+    HelmChart, HelmChartMetadata, HelmDeployStatus, HelmListItem, HelmRelease, HelmReleaseInfo,
+    HelmReleaseMetadata, HelmReleaseStatus, HelmRevision,
+};
+
+/// Namespace used by helm when it isn't specified
+const DEFAULT_NAMESPACE: &str = "default";
+
+/// Stateful in-memory helm, implements blocking and nonblocking `HelmExecutor`
+/// (with `blocking-mock` and `nonblocking-mock` features).
+///
+/// - install adds release with revision 1, upgrade adds next revision and marks
+///   the previous deployed one as superseded
+/// - uninstall removes release with its history
+/// - rollback adds revision with chart and values of target revision
+/// - releases are isolated by namespace, `list` without namespace shows `default` one
+///
+/// Chart version without explicit `chart_version` is resolved from charts registered
+/// with `with_chart`, the latest stable one wins. Inline values are stored as user supplied
+/// values, values files and `--set` overrides are accepted but not evaluated.
+///
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorHelmExecutor(Arc<Mutex<SimulatorState>>);
+
+impl SimulatorHelmExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register chart version available for install and `search_repo`
+    /// - `chart_name` - chart reference. For example: cowboysysop/whoami
+    /// - `version` - chart version. For example: 5.2.0
+    /// - `app_version` - application version. For example: 1.10.3
+    pub fn with_chart(self, chart_name: &str, version: &str, app_version: &str) -> Self {
+        self.state().charts.push(ChartSearchResult {
+            name: chart_name.to_string(),
+            version: version.to_string(),
+            app_version: app_version.to_string(),
+            description: String::new(),
+        });
+        self
+    }
+
+    /// Set status of the latest release revision, e.g. to simulate failed or pending release
+    pub fn set_status(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        status: HelmDeployStatus,
+    ) -> Result<(), HelmWrapperError> {
+        let mut state = self.state();
+
+        let revision = state
+            .revisions_mut(namespace.as_ref(), release_name.as_ref())
+            .and_then(|revisions| revisions.last_mut())
+            .ok_or_else(|| release_not_found("status", namespace, release_name))?;
+
+        revision.status = status;
+
+        Ok(())
+    }
+
+    pub(crate) fn state(&self) -> MutexGuard<'_, SimulatorState> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SimulatorState {
+    /// Release revisions by namespace and release name, oldest first
+    releases: BTreeMap<(String, String), Vec<SimulatedRevision>>,
+    charts: Vec<ChartSearchResult>,
+    repositories: Vec<HelmRepository>,
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedRevision {
+    revision: u32,
+    status: HelmDeployStatus,
+    chart_name: String,
+    chart_version: String,
+    app_version: String,
+    config: serde_json::Value,
+    description: String,
+}
+
+impl SimulatedRevision {
+    fn chart(&self) -> String {
+        format!("{}-{}", self.chart_name, self.chart_version)
+    }
+
+    fn to_release(&self, namespace: &str, release_name: &str) -> HelmRelease {
+        HelmRelease {
+            name: release_name.to_string(),
+            namespace: namespace.to_string(),
+            version: self.revision,
+            info: HelmReleaseInfo {
+                description: self.description.clone(),
+                status: self.status.clone(),
+                ..HelmReleaseInfo::default()
+            },
+            chart: Some(HelmChart {
+                metadata: HelmChartMetadata {
+                    name: self.chart_name.clone(),
+                    version: self.chart_version.clone(),
+                    app_version: Some(self.app_version.clone()),
+                    ..HelmChartMetadata::default()
+                },
+                values: serde_json::Value::Null,
+            }),
+            config: self.config.clone(),
+            ..HelmRelease::default()
+        }
+    }
+
+    fn to_list_item(&self, namespace: &str, release_name: &str) -> HelmListItem {
+        HelmListItem {
+            name: release_name.to_string(),
+            namespace: namespace.to_string(),
+            revision: self.revision.to_string(),
+            updated: String::new(),
+            status: self.status.clone(),
+            chart: self.chart(),
+            app_version: self.app_version.clone(),
+        }
+    }
+
+    fn to_revision(&self) -> HelmRevision {
+        HelmRevision {
+            revision: self.revision,
+            updated: String::new(),
+            status: self.status.clone(),
+            chart: self.chart(),
+            app_version: self.app_version.clone(),
+            description: self.description.clone(),
+        }
+    }
+
+    fn to_metadata(&self, namespace: &str, release_name: &str) -> HelmReleaseMetadata {
+        HelmReleaseMetadata {
+            name: release_name.to_string(),
+            chart: self.chart_name.clone(),
+            version: self.chart_version.clone(),
+            app_version: self.app_version.clone(),
+            namespace: namespace.to_string(),
+            revision: self.revision,
+            status: self.status.clone(),
+            ..HelmReleaseMetadata::default()
+        }
+    }
+}
+
+impl SimulatorState {
+    fn revisions(&self, namespace: &str, release_name: &str) -> Option<&Vec<SimulatedRevision>> {
+        self.releases
+            .get(&(namespace.to_string(), release_name.to_string()))
+    }
+
+    fn revisions_mut(
+        &mut self,
+        namespace: &str,
+        release_name: &str,
+    ) -> Option<&mut Vec<SimulatedRevision>> {
+        self.releases
+            .get_mut(&(namespace.to_string(), release_name.to_string()))
+    }
+
+    fn revision(
+        &self,
+        subcommand: &str,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<&SimulatedRevision, HelmWrapperError> {
+        let revisions = self
+            .revisions(namespace.as_ref(), release_name.as_ref())
+            .ok_or_else(|| release_not_found(subcommand, namespace, release_name))?;
+
+        match revision {
+            Some(revision) => revisions.iter().find(|r| r.revision == revision),
+            None => revisions.last(),
+        }
+        .ok_or_else(|| release_not_found(subcommand, namespace, release_name))
+    }
+
+    pub fn list(&self, namespace: Option<&NonBlankString>) -> Vec<HelmListItem> {
+        let namespace = namespace.map_or(DEFAULT_NAMESPACE, |namespace| namespace.as_ref());
+
+        self.releases
+            .iter()
+            .filter(|((release_namespace, _), _)| release_namespace == namespace)
+            .filter_map(|((release_namespace, release_name), revisions)| {
+                revisions
+                    .last()
+                    .map(|revision| revision.to_list_item(release_namespace, release_name))
+            })
+            .collect()
+    }
+
+    pub fn install_or_upgrade(
+        &mut self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<HelmRelease, HelmWrapperError> {
+        request.validate()?;
+
+        let namespace = request.namespace.to_string();
+        let release_name = request.release_name.to_string();
+
+        let (chart_name, chart_version, app_version) = self.resolve_chart(request)?;
+
+        let previous = self
+            .revisions(&namespace, &release_name)
+            .and_then(|r| r.last());
+
+        if previous.is_some_and(|previous| previous.status.is_pending()) {
+            return Err(failure(
+                "upgrade",
+                Some(&request.namespace),
+                Some(&request.release_name),
+                "Error: UPGRADE FAILED: another operation (install/upgrade/rollback) is in progress",
+            ));
+        }
+
+        let mut config = match previous {
+            Some(previous) if request.reuse_values && !request.reset_values => {
+                previous.config.clone()
+            }
+            _ => serde_json::Value::Null,
+        };
+
+        for source in &request.values {
+            if let ValuesSource::Inline(values) = source {
+                crate::merge_values(&mut config, values);
+            }
+        }
+
+        let dry_run = request.dry_run.is_some();
+
+        let (status, description) = match (previous, dry_run) {
+            (None, false) => (HelmDeployStatus::Deployed, "Install complete"),
+            (Some(_), false) => (HelmDeployStatus::Deployed, "Upgrade complete"),
+            (None, true) => (HelmDeployStatus::PendingInstall, "Dry run complete"),
+            (Some(_), true) => (HelmDeployStatus::PendingUpgrade, "Dry run complete"),
+        };
+
+        let revision = SimulatedRevision {
+            revision: previous.map_or(1, |previous| previous.revision + 1),
+            status,
+            chart_name,
+            chart_version,
+            app_version,
+            config,
+            description: request
+                .description
+                .clone()
+                .unwrap_or_else(|| description.to_string()),
+        };
+
+        let release = revision.to_release(&namespace, &release_name);
+
+        if !dry_run {
+            self.push_revision(namespace, release_name, revision);
+        }
+
+        Ok(release)
+    }
+
+    /// Add new deployed revision, previous deployed revisions become superseded
+    fn push_revision(
+        &mut self,
+        namespace: String,
+        release_name: String,
+        revision: SimulatedRevision,
+    ) {
+        let revisions = self.releases.entry((namespace, release_name)).or_default();
+
+        for previous in revisions.iter_mut() {
+            if previous.status == HelmDeployStatus::Deployed {
+                previous.status = HelmDeployStatus::Superseded;
+            }
+        }
+
+        revisions.push(revision);
+    }
+
+    /// Chart name, version and app version of requested chart
+    fn resolve_chart(
+        &self,
+        request: &InstallOrUpgradeRequest,
+    ) -> Result<(String, String, String), HelmWrapperError> {
+        let reference = ChartReference::parse(request.chart_name.as_ref());
+        let registered_name = reference.without_tag().to_string();
+
+        let base_name = registered_name
+            .rsplit('/')
+            .next()
+            .unwrap_or(&registered_name)
+            .trim_end_matches(".tgz");
+
+        // chart archives are named with version, e.g. whoami-5.2.0.tgz
+        let (chart_name, archive_version) = match reference {
+            ChartReference::Url(_) | ChartReference::Path(_) => {
+                match split_chart_version(base_name) {
+                    Some((chart_name, version)) => (chart_name, Some(version)),
+                    None => (base_name, None),
+                }
+            }
+            _ => (base_name, None),
+        };
+
+        let version = request
+            .chart_version
+            .as_ref()
+            .map(|version| version.as_ref())
+            .or(reference.tag())
+            .or(archive_version);
+
+        let registered = self
+            .charts
+            .iter()
+            .filter(|chart| chart.name == registered_name)
+            .filter(|chart| version.is_none_or(|version| chart.version == version))
+            .filter_map(|chart| Version::parse(&chart.version).ok().map(|v| (v, chart)))
+            // like helm without --devel, latest stable version is used
+            .filter(|(chart_version, _)| version.is_some() || chart_version.pre.is_empty())
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, chart)| chart);
+
+        match (registered, version) {
+            (Some(chart), _) => Ok((
+                chart_name.to_string(),
+                chart.version.clone(),
+                chart.app_version.clone(),
+            )),
+            (None, Some(version))
+                if !self
+                    .charts
+                    .iter()
+                    .any(|chart| chart.name == registered_name) =>
+            {
+                Ok((chart_name.to_string(), version.to_string(), String::new()))
+            }
+            (None, version) => Err(failure(
+                "upgrade",
+                Some(&request.namespace),
+                Some(&request.release_name),
+                &match version {
+                    Some(version) => {
+                        format!("Error: no chart version found for {chart_name}-{version}")
+                    }
+                    None => format!("Error: no chart name found for {chart_name}"),
+                },
+            )),
+        }
+    }
+
+    pub fn uninstall(
+        &mut self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+    ) -> Result<(), HelmWrapperError> {
+        self.releases
+            .remove(&(namespace.to_string(), release_name.to_string()))
+            .map(|_| ())
+            .ok_or_else(|| {
+                failure(
+                    "uninstall",
+                    Some(namespace),
+                    Some(release_name),
+                    &format!(
+                        "Error: uninstall: Release not loaded: {release_name}: release: not found"
+                    ),
+                )
+            })
+    }
+
+    pub fn rollback(
+        &mut self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<u32, HelmWrapperError> {
+        let latest = self.revision("rollback", namespace, release_name, None)?;
+
+        let revision = match revision {
+            Some(revision) => revision,
+            None => latest
+                .revision
+                .checked_sub(1)
+                .filter(|revision| *revision > 0)
+                .ok_or_else(|| {
+                    HelmWrapperError::RollbackRevisionNotFound(release_name.to_string())
+                })?,
+        };
+
+        let next_revision = latest.revision + 1;
+
+        let target = self
+            .revision("rollback", namespace, release_name, Some(revision))
+            .map_err(|_| {
+                failure(
+                    "rollback",
+                    Some(namespace),
+                    Some(release_name),
+                    &format!("Error: release has no {revision} version"),
+                )
+            })?;
+
+        let rolled_back = SimulatedRevision {
+            revision: next_revision,
+            status: HelmDeployStatus::Deployed,
+            description: format!("Rollback to {revision}"),
+            ..target.clone()
+        };
+
+        self.push_revision(namespace.to_string(), release_name.to_string(), rolled_back);
+
+        Ok(revision)
+    }
+
+    pub fn history(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        max: Option<u32>,
+    ) -> Result<Vec<HelmRevision>, HelmWrapperError> {
+        let revisions = self
+            .revisions(namespace.as_ref(), release_name.as_ref())
+            .ok_or_else(|| release_not_found("history", namespace, release_name))?;
+
+        let skip = max.map_or(0, |max| revisions.len().saturating_sub(max as usize));
+
+        Ok(revisions
+            .iter()
+            .skip(skip)
+            .map(SimulatedRevision::to_revision)
+            .collect())
+    }
+
+    pub fn status(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseStatus, HelmWrapperError> {
+        self.revision("status", namespace, release_name, revision)
+            .map(|r| {
+                r.to_release(namespace.as_ref(), release_name.as_ref())
+                    .into()
+            })
+    }
+
+    pub fn get_values(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<serde_json::Value, HelmWrapperError> {
+        self.revision("get", namespace, release_name, revision)
+            .map(|r| r.config.clone())
+    }
+
+    pub fn get_metadata(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<HelmReleaseMetadata, HelmWrapperError> {
+        self.revision("get", namespace, release_name, revision)
+            .map(|r| r.to_metadata(namespace.as_ref(), release_name.as_ref()))
+    }
+
+    /// Check that release revision exists, for `helm get` of data which isn't simulated
+    pub fn ensure_revision(
+        &self,
+        namespace: &NonBlankString,
+        release_name: &NonBlankString,
+        revision: Option<u32>,
+    ) -> Result<(), HelmWrapperError> {
+        self.revision("get", namespace, release_name, revision)
+            .map(|_| ())
+    }
+
+    pub fn repo_add(
+        &mut self,
+        name: &NonBlankString,
+        url: &NonBlankString,
+        options: &RepoAddOptions,
+    ) -> Result<(), HelmWrapperError> {
+        let repository = HelmRepository {
+            name: name.to_string(),
+            url: url.to_string(),
+        };
+
+        match self.repositories.iter_mut().find(|r| r.name == repository.name) {
+            Some(existing) if existing.url == repository.url || options.force_update => {
+                *existing = repository;
+                Ok(())
+            }
+            Some(_) => Err(failure(
+                "repo",
+                None,
+                None,
+                &format!(
+                    "Error: repository name ({name}) already exists, please specify a different name"
+                ),
+            )),
+            None => {
+                self.repositories.push(repository);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn repo_update(&self, names: &[NonBlankString]) -> Result<(), HelmWrapperError> {
+        if self.repositories.is_empty() {
+            return Err(failure(
+                "repo",
+                None,
+                None,
+                "Error: no repositories found. You must add one before updating",
+            ));
+        }
+
+        let unknown: Vec<&str> = names
+            .iter()
+            .map(|name| name.as_ref())
+            .filter(|name| !self.repositories.iter().any(|r| r.name == *name))
+            .collect();
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(failure(
+                "repo",
+                None,
+                None,
+                &format!(
+                    "Error: no repositories found matching '{}'.  Nothing will be updated",
+                    unknown.join(", ")
+                ),
+            ))
+        }
+    }
+
+    pub fn repo_list(&self) -> Vec<HelmRepository> {
+        self.repositories.clone()
+    }
+
+    pub fn repo_remove(&mut self, name: &NonBlankString) -> Result<(), HelmWrapperError> {
+        let count = self.repositories.len();
+        self.repositories.retain(|r| r.name != name.as_ref());
+
+        if self.repositories.len() < count {
+            Ok(())
+        } else {
+            Err(failure(
+                "repo",
+                None,
+                None,
+                &format!("Error: no repo named \"{name}\" found"),
+            ))
+        }
+    }
+
+    /// Registered charts which names contain keyword, newest versions first
+    pub fn search_repo(
+        &self,
+        keyword: &NonBlankString,
+        versions: bool,
+        devel: bool,
+        version_constraint: Option<&NonBlankString>,
+    ) -> Result<Vec<ChartSearchResult>, HelmWrapperError> {
+        let constraint = match version_constraint {
+            Some(constraint) => Some(VersionReq::parse(constraint.as_ref()).map_err(|e| {
+                failure(
+                    "search",
+                    None,
+                    None,
+                    &format!("Error: invalid constraint \"{constraint}\": {e}"),
+                )
+            })?),
+            None => None,
+        };
+
+        let keyword = keyword.as_ref().to_lowercase();
+
+        let mut results: Vec<(Version, &ChartSearchResult)> = self
+            .charts
+            .iter()
+            .filter(|chart| chart.name.to_lowercase().contains(&keyword))
+            .filter_map(|chart| Version::parse(&chart.version).ok().map(|v| (v, chart)))
+            .filter(|(version, _)| devel || version.pre.is_empty())
+            .filter(|(version, _)| constraint.as_ref().is_none_or(|c| c.matches(version)))
+            .collect();
+
+        results.sort_by(|(a, a_chart), (b, b_chart)| {
+            a_chart.name.cmp(&b_chart.name).then_with(|| b.cmp(a))
+        });
+
+        if !versions {
+            results.dedup_by(|(_, a), (_, b)| a.name == b.name);
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|(_, chart)| chart.clone())
+            .collect())
+    }
+}
+
+fn failure(
+    subcommand: &str,
+    namespace: Option<&NonBlankString>,
+    release_name: Option<&NonBlankString>,
+    stderr: &str,
+) -> HelmWrapperError {
+    HelmWrapperError::from_failure(HelmCommandFailure {
+        subcommand: subcommand.to_string(),
+        release_name: release_name.map(|release_name| release_name.to_string()),
+        namespace: namespace.map(|namespace| namespace.to_string()),
+        exit_code: Some(1),
+        stdout: String::new(),
+        stderr: stderr.to_string(),
+    })
+}
+
+fn release_not_found(
+    subcommand: &str,
+    namespace: &NonBlankString,
+    release_name: &NonBlankString,
+) -> HelmWrapperError {
+    failure(
+        subcommand,
+        Some(namespace),
+        Some(release_name),
+        "Error: release: not found",
+    )
+}
+
+#[cfg(all(test, feature = "blocking-mock"))]
+mod simulator_tests {
+    use non_blank_string_rs::NonBlankString;
+    use serde_json::json;
+
+    use crate::{
+        blocking::HelmExecutor,
+        error::HelmWrapperError,
+        repo::RepoAddOptions,
+        request::{DryRun, InstallOrUpgradeRequest},
+        simulator::SimulatorHelmExecutor,
+        HelmDeployStatus, RollbackOptions,
+    };
+
+    fn name(value: &str) -> NonBlankString {
+        value.parse().unwrap()
+    }
+
+    fn simulator() -> SimulatorHelmExecutor {
+        SimulatorHelmExecutor::new()
+            .with_chart("cowboysysop/whoami", "5.1.0", "1.10.2")
+            .with_chart("cowboysysop/whoami", "5.2.0", "1.10.3")
+            .with_chart("cowboysysop/whoami", "6.0.0-rc.1", "1.11.0")
+    }
+
+    fn request(namespace: &str) -> InstallOrUpgradeRequest {
+        InstallOrUpgradeRequest::new(name(namespace), name("whoami"), name("cowboysysop/whoami"))
+    }
+
+    #[test]
+    fn install_upgrade_and_uninstall() {
+        let executor = simulator();
+        let namespace = name("apps");
+
+        let release = executor.install_or_upgrade_with(&request("apps")).unwrap();
+
+        assert_eq!(release.version, 1);
+        assert_eq!(release.info.status, HelmDeployStatus::Deployed);
+        assert_eq!(release.chart.unwrap().metadata.version, "5.2.0");
+
+        let release = executor
+            .install_or_upgrade_with(&request("apps").chart_version(name("5.1.0")))
+            .unwrap();
+
+        assert_eq!(release.version, 2);
+        assert_eq!(release.info.description, "Upgrade complete");
+
+        let releases = executor.list(Some(&namespace)).unwrap();
+
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].revision, "2");
+        assert_eq!(releases[0].chart, "whoami-5.1.0");
+        assert_eq!(releases[0].app_version, "1.10.2");
+
+        let history = executor.history(&namespace, &name("whoami"), None).unwrap();
+        let statuses: Vec<HelmDeployStatus> = history.into_iter().map(|r| r.status).collect();
+
+        assert_eq!(
+            statuses,
+            vec![HelmDeployStatus::Superseded, HelmDeployStatus::Deployed]
+        );
+
+        executor.uninstall(&namespace, &name("whoami")).unwrap();
+
+        assert!(executor.list(Some(&namespace)).unwrap().is_empty());
+        assert!(matches!(
+            executor.status(&namespace, &name("whoami"), None),
+            Err(HelmWrapperError::ReleaseNotFound(_))
+        ));
+        assert!(matches!(
+            executor.uninstall(&namespace, &name("whoami")),
+            Err(HelmWrapperError::ReleaseNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn namespaces_are_isolated() {
+        let executor = simulator();
+
+        executor.install_or_upgrade_with(&request("apps")).unwrap();
+        executor.install_or_upgrade_with(&request("apps")).unwrap();
+        executor
+            .install_or_upgrade_with(&request("default"))
+            .unwrap();
+
+        let other = executor
+            .status(&name("default"), &name("whoami"), None)
+            .unwrap();
+
+        assert_eq!(other.revision, 1);
+        assert_eq!(executor.list(None).unwrap()[0].namespace, "default");
+        assert!(executor.list(Some(&name("other"))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rollback_to_previous_revision() {
+        let executor = simulator();
+        let namespace = name("apps");
+
+        executor
+            .install_or_upgrade_with(
+                &request("apps")
+                    .chart_version(name("5.1.0"))
+                    .values_json(json!({"replicaCount": 1})),
+            )
+            .unwrap();
+        executor
+            .install_or_upgrade_with(&request("apps").values_json(json!({"replicaCount": 2})))
+            .unwrap();
+
+        let revision = executor
+            .rollback(
+                &namespace,
+                &name("whoami"),
+                None,
+                &RollbackOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(revision, 1);
+
+        let release = executor.status(&namespace, &name("whoami"), None).unwrap();
+
+        assert_eq!(release.revision, 3);
+        assert_eq!(release.info.description, "Rollback to 1");
+        assert_eq!(release.chart.unwrap().version, "5.1.0");
+        assert_eq!(
+            executor
+                .get_values(&namespace, &name("whoami"), None, false)
+                .unwrap(),
+            json!({"replicaCount": 1})
+        );
+        assert_eq!(
+            executor
+                .get_values(&namespace, &name("whoami"), Some(2), false)
+                .unwrap(),
+            json!({"replicaCount": 2})
+        );
+        assert!(matches!(
+            executor.rollback(
+                &namespace,
+                &name("whoami"),
+                Some(9),
+                &RollbackOptions::default()
+            ),
+            Err(HelmWrapperError::CommandFailed(_))
+        ));
+    }
+
+    #[test]
+    fn failed_operations() {
+        let executor = simulator();
+
+        assert!(matches!(
+            executor.install_or_upgrade_with(&request("apps").chart_version(name("9.9.9"))),
+            Err(HelmWrapperError::ChartNotFound(_))
+        ));
+
+        let dry_run = executor
+            .install_or_upgrade_with(&request("apps").dry_run(DryRun::Client))
+            .unwrap();
+
+        assert_eq!(dry_run.info.status, HelmDeployStatus::PendingInstall);
+        assert!(executor.list(Some(&name("apps"))).unwrap().is_empty());
+
+        executor.install_or_upgrade_with(&request("apps")).unwrap();
+        executor
+            .set_status(
+                &name("apps"),
+                &name("whoami"),
+                HelmDeployStatus::PendingUpgrade,
+            )
+            .unwrap();
+
+        assert!(matches!(
+            executor.install_or_upgrade_with(&request("apps")),
+            Err(HelmWrapperError::OperationInProgress(_))
+        ));
+    }
+
+    #[test]
+    fn repositories_and_search() {
+        let executor = simulator();
+
+        assert!(executor.repo_update(&[]).is_err());
+
+        executor
+            .repo_add(
+                &name("cowboysysop"),
+                &name("https://cowboysysop.github.io/charts/"),
+                &RepoAddOptions::default(),
+            )
+            .unwrap();
+
+        assert!(executor
+            .repo_add(
+                &name("cowboysysop"),
+                &name("https://example.com"),
+                &RepoAddOptions::default()
+            )
+            .is_err());
+        assert!(executor.repo_update(&[name("cowboysysop")]).is_ok());
+        assert!(executor.repo_update(&[name("unknown")]).is_err());
+        assert_eq!(executor.repo_list().unwrap().len(), 1);
+
+        let latest = executor
+            .search_repo(&name("whoami"), false, false, None)
+            .unwrap();
+
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].version, "5.2.0");
+
+        let versions: Vec<String> = executor
+            .search_repo(&name("whoami"), true, true, Some(&name("<6.0.0")))
+            .unwrap()
+            .into_iter()
+            .map(|result| result.version)
+            .collect();
+
+        assert_eq!(versions, vec!["5.2.0", "5.1.0"]);
+
+        executor.repo_remove(&name("cowboysysop")).unwrap();
+
+        assert!(executor.repo_remove(&name("cowboysysop")).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "nonblocking-mock"))]
+mod nonblocking_simulator_tests {
+    use crate::{
+        nonblocking::HelmExecutor, request::InstallOrUpgradeRequest,
+        simulator::SimulatorHelmExecutor,
+    };
+
+    #[tokio::test]
+    async fn clones_share_state() {
+        let executor = SimulatorHelmExecutor::new();
+        let clone = executor.clone();
+
+        let request = InstallOrUpgradeRequest::new(
+            "apps".parse().unwrap(),
+            "whoami".parse().unwrap(),
+            "./whoami-5.2.0.tgz".parse().unwrap(),
+        );
+
+        tokio::spawn(async move { clone.install_or_upgrade_with(&request).await })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let releases = executor.list(Some(&"apps".parse().unwrap())).await.unwrap();
+
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].chart, "whoami-5.2.0");
+    }
+}
@@ -1,6 +1,14 @@
 use log::LevelFilter;
 use non_blank_string_rs::NonBlankString;
 
+/// Helm executable, `HELM_PATH` allows to run tests against `fake-helm`
+pub fn get_test_helm_path() -> NonBlankString {
+    std::env::var("HELM_PATH")
+        .ok()
+        .and_then(|path| path.parse().ok())
+        .unwrap_or_else(|| "helm".parse().unwrap())
+}
+
 pub fn get_test_namespace() -> NonBlankString {
     "whoami".parse().unwrap()
 }
@@ -13,6 +21,14 @@ pub fn get_test_chart_name() -> NonBlankString {
     "cowboysysop/whoami".parse().unwrap()
 }
 
+pub fn get_test_repo_name() -> NonBlankString {
+    "cowboysysop".parse().unwrap()
+}
+
+pub fn get_test_repo_url() -> NonBlankString {
+    "https://cowboysysop.github.io/charts/".parse().unwrap()
+}
+
 pub fn get_test_helm_options() -> Vec<NonBlankString> {
     vec!["--create-namespace".parse().unwrap()]
 }